@@ -1,20 +1,32 @@
 use crate::{
-    chat::{ChatExporter, ChatMessage, ChatProcessor, ChatSession, DocumentProcessor},
+    chat::{ChatExporter, ChatMessage, ChatProcessor, DocumentProcessor},
+    config::Config,
     decision::DecisionMaker,
     dialog,
     experience::Experience,
+    llm::LlmRegistry,
     memory::SharedMemory,
     ollama::OllamaClient,
     pattern::PatternRecognizer,
     personality::Personality,
+    qlearning::QLearner,
+    rate_limit::RateLimiter,
+    realtime::SessionRegistry,
+    sentiment::SentimentLexicon,
+    session_store::HistoryPage,
+    storage::Storage,
 };
 use axum::{
-    extract::{Path, Query, State},
+    extract::{
+        ws::{WebSocket, WebSocketUpgrade},
+        Multipart, Path, Query, State,
+    },
     http::StatusCode,
+    response::IntoResponse,
     Json,
 };
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
 
 // ============ Application State ============
 
@@ -22,11 +34,47 @@ use std::sync::{Arc, RwLock};
 pub struct AppState {
     pub memory: SharedMemory,
     pub ollama: Arc<OllamaClient>,
+    pub config: Arc<Config>,
+    pub llm_registry: Arc<LlmRegistry>,
+    pub storage: Arc<dyn Storage>,
+    pub realtime: Arc<SessionRegistry>,
+    pub rate_limiter: Arc<RateLimiter>,
+    pub qlearner: Arc<QLearner>,
+    pub sentiment_lexicon: Arc<SentimentLexicon>,
 }
 
 impl AppState {
-    pub fn new(memory: SharedMemory, ollama: Arc<OllamaClient>) -> Self {
-        Self { memory, ollama }
+    pub fn new(
+        memory: SharedMemory,
+        ollama: Arc<OllamaClient>,
+        config: Arc<Config>,
+        storage: Arc<dyn Storage>,
+        llm_registry: Arc<LlmRegistry>,
+        realtime: Arc<SessionRegistry>,
+        rate_limiter: Arc<RateLimiter>,
+        qlearner: Arc<QLearner>,
+        sentiment_lexicon: Arc<SentimentLexicon>,
+    ) -> Self {
+        Self {
+            memory,
+            ollama,
+            config,
+            llm_registry,
+            storage,
+            realtime,
+            rate_limiter,
+            qlearner,
+            sentiment_lexicon,
+        }
+    }
+
+    /// Build a `ChatProcessor` backed by whichever `LlmProvider` is registered
+    /// under `config.llm_provider`, falling back to Ollama if it isn't.
+    fn chat_processor(&self) -> ChatProcessor {
+        match self.llm_registry.get(&self.config.llm_provider) {
+            Some(provider) => ChatProcessor::with_provider(provider),
+            None => ChatProcessor::with_ollama(self.ollama.clone()),
+        }
     }
 }
 
@@ -66,6 +114,69 @@ pub struct SearchQuery {
     pub q: String,
 }
 
+/// A cursor-paginated page of items, CHATHISTORY-style, with cursors for
+/// the adjacent pages so clients can scroll incrementally instead of
+/// re-fetching the whole collection.
+#[derive(Debug, Serialize)]
+pub struct PaginatedResponse<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+/// `?before=<id|timestamp>&after=<id|timestamp>&limit=N` query params shared
+/// by the paginated history/experience endpoints. Experience endpoints accept
+/// an experience id or an RFC3339 timestamp; chat history accepts message ids only.
+#[derive(Debug, Deserialize)]
+pub struct HistoryRangeQuery {
+    pub before: Option<String>,
+    pub after: Option<String>,
+    #[serde(default = "default_history_count")]
+    pub limit: usize,
+}
+
+/// `?before&after&limit&tag&created_after&sort=asc|desc` query params for
+/// paginated, filterable learning-record listing
+#[derive(Debug, Deserialize)]
+pub struct RecordListQuery {
+    pub before: Option<String>,
+    pub after: Option<String>,
+    #[serde(default = "default_history_count")]
+    pub limit: usize,
+    pub tag: Option<String>,
+    /// RFC3339 timestamp; records with `learned_at` at or before this are excluded
+    pub created_after: Option<String>,
+    #[serde(default)]
+    pub sort: SortOrder,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// `?q&before&after&limit` query params for paginated learning-record search
+#[derive(Debug, Deserialize)]
+pub struct SearchPageQuery {
+    pub q: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    #[serde(default = "default_history_count")]
+    pub limit: usize,
+}
+
+/// `?before&after&limit` query params for paginated chat-session listing
+#[derive(Debug, Deserialize)]
+pub struct SessionListQuery {
+    pub before: Option<String>,
+    pub after: Option<String>,
+    #[serde(default = "default_history_count")]
+    pub limit: usize,
+}
+
 // ============ Handlers ============
 
 /// Health check endpoint
@@ -77,24 +188,38 @@ pub async fn health_check() -> Json<ApiResponse<String>> {
     })
 }
 
-/// Get all experiences
+/// Get experiences, windowed by an optional before/after cursor (experience
+/// id or RFC3339 timestamp) and limit, instead of dumping the whole collection
 pub async fn get_experiences(
     State(state): State<AppState>,
-) -> Result<Json<ApiResponse<Vec<Experience>>>, StatusCode> {
+    Query(params): Query<HistoryRangeQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<Experience>>>, StatusCode> {
     let mem = state.memory.read().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
+
     if mem.is_empty() {
         return Ok(Json(ApiResponse {
             success: true,
-            data: Some(vec![]),
+            data: Some(PaginatedResponse {
+                items: vec![],
+                next_cursor: None,
+                prev_cursor: None,
+            }),
             message: "No experiences found. Memory is empty.".to_string(),
         }));
     }
-    
+
+    let (items, next_cursor, prev_cursor) = mem
+        .paginate_experiences(params.before.as_deref(), params.after.as_deref(), params.limit)
+        .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+
     Ok(Json(ApiResponse {
         success: true,
-        data: Some(mem.get_experiences().to_vec()),
-        message: format!("Retrieved {} experiences", mem.experiences_len()),
+        message: format!("Retrieved {} experiences", items.len()),
+        data: Some(PaginatedResponse {
+            items,
+            next_cursor,
+            prev_cursor,
+        }),
     }))
 }
 
@@ -125,6 +250,13 @@ pub async fn create_experience(
     } else {
         Experience::new(&payload.content, &payload.source)
     };
+    // Best-effort: a failed embed (e.g. Ollama disabled or unreachable)
+    // just leaves the experience out of semantic search rather than
+    // failing the whole request.
+    let embedding = state.ollama.embed(&payload.content).await.ok();
+    let exp = exp.with_embedding(embedding);
+
+    state.storage.add_experience(exp.clone()).await;
 
     let mut mem = state.memory.write().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     mem.remember(exp.clone());
@@ -152,6 +284,71 @@ pub async fn search_experiences(
     }))
 }
 
+#[derive(Debug, Serialize)]
+pub struct FullTextSearchResult {
+    pub experience: Experience,
+    pub score: f32,
+}
+
+/// Search experiences with a BM25-ranked inverted index (typo-tolerant)
+pub async fn search_experiences_fulltext(
+    State(state): State<AppState>,
+    Query(params): Query<SearchQuery>,
+) -> Result<Json<ApiResponse<Vec<FullTextSearchResult>>>, StatusCode> {
+    let mem = state.memory.read().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let results: Vec<FullTextSearchResult> = mem
+        .search_bm25(&params.q, 10)
+        .into_iter()
+        .map(|(exp, score)| FullTextSearchResult {
+            experience: exp.clone(),
+            score,
+        })
+        .collect();
+
+    Ok(Json(ApiResponse {
+        success: true,
+        message: format!("Found {} matching experiences", results.len()),
+        data: Some(results),
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct SemanticSearchResult {
+    pub experience: Experience,
+    pub score: f32,
+}
+
+/// Search experiences by embedding similarity (cosine similarity over stored,
+/// precomputed Ollama embeddings, highest first)
+pub async fn search_experiences_semantic(
+    State(state): State<AppState>,
+    Query(params): Query<SearchQuery>,
+) -> Result<Json<ApiResponse<Vec<SemanticSearchResult>>>, StatusCode> {
+    let query_embedding = state
+        .ollama
+        .embed(&params.q)
+        .await
+        .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let mem = state.memory.read().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let matches = mem.search_semantic(&query_embedding, 10);
+
+    let results: Vec<SemanticSearchResult> = matches
+        .into_iter()
+        .map(|m| SemanticSearchResult {
+            experience: m.experience.clone(),
+            score: m.score,
+        })
+        .collect();
+
+    Ok(Json(ApiResponse {
+        success: true,
+        message: format!("Found {} semantically similar experiences", results.len()),
+        data: Some(results),
+    }))
+}
+
 /// Get statistics and patterns
 pub async fn get_stats(
     State(state): State<AppState>,
@@ -230,6 +427,8 @@ pub async fn make_decision_for_query(
 pub async fn clear_memory(
     State(state): State<AppState>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    state.storage.clear_experiences().await;
+
     let mut mem = state.memory.write().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     mem.clear();
 
@@ -283,7 +482,11 @@ pub struct PersonalityResponse {
     pub curiosity: f32,
     pub happiness: f32,
     pub caution: f32,
+    pub affinity: i32,
+    pub attitude: String,
     pub dominant_trait: String,
+    pub mental_stability: u8,
+    pub emotional_tier: String,
     pub influenced_response: String,
 }
 
@@ -305,17 +508,34 @@ pub async fn update_personality(
         patterns.analyze(exp);
     }
     
-    let mut personality = Personality::new();
+    let mut personality = Personality::new()
+        .with_qlearner(state.qlearner.clone())
+        .with_lexicon(state.sentiment_lexicon.clone());
+    let style = personality.choose_style();
+
+    let affinity_before = personality.affinity;
     personality.update(&payload.input, &mem, &patterns);
-    
-    let influenced = personality.influence_response(&payload.response);
+
+    // Reward the style chosen for this turn using how much the user's
+    // message moved `affinity` as a proxy for their sentiment, since that's
+    // the only signal `update()` already derives from the input text.
+    let reward = ((personality.affinity - affinity_before) as f32 / 10.0).clamp(-1.0, 1.0);
+    personality.learn(reward);
+
+    let influenced = personality.influence_response(&payload.response, style);
     let dominant = personality.dominant_trait().to_string();
-    
+    let attitude = personality.attitude_label().to_string();
+    let emotional_tier = personality.emotional_tier().to_string();
+
     let response = PersonalityResponse {
         curiosity: personality.curiosity,
         happiness: personality.happiness,
         caution: personality.caution,
+        affinity: personality.affinity,
+        attitude,
         dominant_trait: dominant,
+        mental_stability: personality.mental_stability(),
+        emotional_tier,
         influenced_response: influenced,
     };
 
@@ -326,12 +546,6 @@ pub async fn update_personality(
     }))
 }
 
-#[derive(Debug, Serialize)]
-pub struct ReflectionResponse {
-    pub total_experiences: usize,
-    pub experiences: Vec<ReflectionItem>,
-}
-
 #[derive(Debug, Serialize)]
 pub struct ReflectionItem {
     pub id: String,
@@ -340,18 +554,23 @@ pub struct ReflectionItem {
     pub content: String,
 }
 
-/// Get reflection view of all experiences
+/// Get a reflection view of experiences, windowed by an optional before/after
+/// cursor (experience id or RFC3339 timestamp) and limit
 pub async fn reflect_memory(
     State(state): State<AppState>,
-) -> Result<Json<ApiResponse<ReflectionResponse>>, StatusCode> {
+    Query(params): Query<HistoryRangeQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<ReflectionItem>>>, StatusCode> {
     let mem = state.memory.read().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
+
     // Use reflect method for logging
     tracing::info!("Memory reflection requested:");
     mem.reflect();
-    
-    let experiences: Vec<ReflectionItem> = mem
-        .get_experiences()
+
+    let (experiences, next_cursor, prev_cursor) = mem
+        .paginate_experiences(params.before.as_deref(), params.after.as_deref(), params.limit)
+        .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+
+    let items: Vec<ReflectionItem> = experiences
         .iter()
         .map(|e| ReflectionItem {
             id: e.id.clone(),
@@ -360,16 +579,15 @@ pub async fn reflect_memory(
             content: e.content.clone(),
         })
         .collect();
-    
-    let response = ReflectionResponse {
-        total_experiences: mem.experiences_len(),
-        experiences,
-    };
 
     Ok(Json(ApiResponse {
         success: true,
-        data: Some(response),
-        message: format!("Reflected on {} experiences", mem.experiences_len()),
+        message: format!("Reflected on {} experiences", items.len()),
+        data: Some(PaginatedResponse {
+            items,
+            next_cursor,
+            prev_cursor,
+        }),
     }))
 }
 
@@ -499,15 +717,6 @@ pub struct UpdateLearningRecordRequest {
     pub summary: Option<String>,
 }
 
-// Global chat sessions storage (in a real app, use a database)
-lazy_static::lazy_static! {
-    static ref CHAT_SESSIONS: Arc<RwLock<std::collections::HashMap<String, ChatSession>>> = 
-        Arc::new(RwLock::new(std::collections::HashMap::new()));
-    
-    static ref API_LEARNING_RECORDS: Arc<RwLock<std::collections::HashMap<String, crate::chat::ApiLearningRecord>>> = 
-        Arc::new(RwLock::new(std::collections::HashMap::new()));
-}
-
 // ============ Chat Handlers ============
 
 /// Send a chat message and get AI response
@@ -517,23 +726,29 @@ pub async fn send_chat_message(
 ) -> Result<Json<ApiResponse<ChatMessageResponse>>, StatusCode> {
     // Get or create session
     let session_id = payload.session_id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
-    
+
+    // Fetch prior turns before recording the new user message, so the LLM call
+    // sees history that doesn't already include it
+    let history = state
+        .storage
+        .get_session(&session_id)
+        .await
+        .map(|s| s.messages)
+        .unwrap_or_default();
+
     // Add user message to session
-    {
-        let mut sessions = CHAT_SESSIONS.write().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        let session = sessions.entry(session_id.clone()).or_insert_with(|| ChatSession::new(&session_id));
-        session.add_message(ChatMessage::user(&payload.content));
-    }
-    
+    let user_message = ChatMessage::user(&payload.content);
+    state.storage.add_message(&session_id, user_message.clone()).await;
+    state.realtime.broadcast(&session_id, &user_message);
+
     // Collect data for processing (outside of locks)
-    let (experiences, user_content, ollama_client) = {
+    let (experiences, user_content) = {
         let mem = state.memory.read().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
         let experiences: Vec<Experience> = mem.get_experiences().to_vec();
         let user_content = payload.content.clone();
-        let ollama_client = state.ollama.clone();
-        (experiences, user_content, ollama_client)
+        (experiences, user_content)
     };
-    
+
     // Process with AI (no locks held during await)
     let ai_message = {
         use crate::memory::Memory;
@@ -542,20 +757,18 @@ pub async fn send_chat_message(
         for exp in temp_memory.get_experiences() {
             patterns.analyze(exp);
         }
-        
-        let processor = ChatProcessor::with_ollama(ollama_client);
-        processor.process_message(&user_content, &temp_memory, &mut patterns).await
+
+        let processor = state.chat_processor();
+        processor
+            .process_message(&user_content, &temp_memory, &mut patterns, &history)
+            .await
     };
     
     // Add AI response to session
     let context_count = ai_message.context_used.as_ref().map(|c| c.len()).unwrap_or(0);
-    {
-        let mut sessions = CHAT_SESSIONS.write().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        if let Some(session) = sessions.get_mut(&session_id) {
-            session.add_message(ai_message.clone());
-        }
-    }
-    
+    state.storage.add_message(&session_id, ai_message.clone()).await;
+    state.realtime.broadcast(&session_id, &ai_message);
+
     Ok(Json(ApiResponse {
         success: true,
         data: Some(ChatMessageResponse {
@@ -567,22 +780,239 @@ pub async fn send_chat_message(
     }))
 }
 
-/// Get chat history for a session
+/// Send a chat message and stream the AI response back as incremental chunked text.
+/// The user message is recorded immediately; the assembled assistant message is
+/// recorded once the upstream stream completes.
+pub async fn stream_chat_message(
+    State(state): State<AppState>,
+    Json(payload): Json<ChatMessageRequest>,
+) -> Result<axum::response::Response, StatusCode> {
+    use futures_util::StreamExt;
+
+    let session_id = payload.session_id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let history = state
+        .storage
+        .get_session(&session_id)
+        .await
+        .map(|s| s.messages)
+        .unwrap_or_default();
+    let user_message = ChatMessage::user(&payload.content);
+    state.storage.add_message(&session_id, user_message.clone()).await;
+    state.realtime.broadcast(&session_id, &user_message);
+
+    let (context_ids, token_stream) = {
+        let mem = state.memory.read().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let processor = state.chat_processor();
+        processor
+            .process_message_stream(&payload.content, &mem, &history)
+            .await
+            .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?
+    };
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<bytes::Bytes, std::io::Error>>(16);
+    let storage = state.storage.clone();
+    let realtime = state.realtime.clone();
+
+    tokio::spawn(async move {
+        let mut full_text = String::new();
+        let mut token_stream = token_stream;
+        while let Some(chunk) = token_stream.next().await {
+            match chunk {
+                Ok(text) => {
+                    full_text.push_str(&text);
+                    if tx.send(Ok(bytes::Bytes::from(text))).await.is_err() {
+                        return;
+                    }
+                }
+                Err(e) => {
+                    let _ = tx.send(Err(std::io::Error::new(std::io::ErrorKind::Other, e))).await;
+                    return;
+                }
+            }
+        }
+        let assistant_message = ChatMessage::with_context("assistant", full_text, context_ids);
+        storage.add_message(&session_id, assistant_message.clone()).await;
+        realtime.broadcast(&session_id, &assistant_message);
+    });
+
+    let body = axum::body::Body::from_stream(tokio_stream::wrappers::ReceiverStream::new(rx));
+
+    Ok(axum::response::Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "text/plain; charset=utf-8")
+        .body(body)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?)
+}
+
+/// Send a chat message and stream the AI response as Server-Sent Events.
+/// Emits a `session` event with the session id, one `token` event per chunk,
+/// and a final `done` event once the assembled assistant message is recorded.
+pub async fn send_chat_message_sse(
+    State(state): State<AppState>,
+    Json(payload): Json<ChatMessageRequest>,
+) -> Result<
+    axum::response::sse::Sse<impl futures_util::Stream<Item = Result<axum::response::sse::Event, std::convert::Infallible>>>,
+    StatusCode,
+> {
+    use axum::response::sse::{Event, Sse};
+    use futures_util::StreamExt;
+
+    let session_id = payload.session_id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let history = state
+        .storage
+        .get_session(&session_id)
+        .await
+        .map(|s| s.messages)
+        .unwrap_or_default();
+    let user_message = ChatMessage::user(&payload.content);
+    state.storage.add_message(&session_id, user_message.clone()).await;
+    state.realtime.broadcast(&session_id, &user_message);
+
+    let (context_ids, token_stream) = {
+        let mem = state.memory.read().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let processor = state.chat_processor();
+        processor
+            .process_message_stream(&payload.content, &mem, &history)
+            .await
+            .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?
+    };
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<Event, std::convert::Infallible>>(16);
+    let storage = state.storage.clone();
+    let realtime = state.realtime.clone();
+    let sse_session_id = session_id.clone();
+
+    tokio::spawn(async move {
+        let _ = tx
+            .send(Ok(Event::default().event("session").data(sse_session_id.clone())))
+            .await;
+
+        let mut full_text = String::new();
+        let mut token_stream = token_stream;
+        while let Some(chunk) = token_stream.next().await {
+            match chunk {
+                Ok(text) => {
+                    full_text.push_str(&text);
+                    if tx.send(Ok(Event::default().event("token").data(text))).await.is_err() {
+                        return;
+                    }
+                }
+                Err(e) => {
+                    let _ = tx.send(Ok(Event::default().event("error").data(e))).await;
+                    return;
+                }
+            }
+        }
+
+        let assistant_message = ChatMessage::with_context("assistant", full_text, context_ids);
+        storage.add_message(&sse_session_id, assistant_message.clone()).await;
+        realtime.broadcast(&sse_session_id, &assistant_message);
+        let _ = tx.send(Ok(Event::default().event("done").data(""))).await;
+    });
+
+    Ok(Sse::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
+}
+
+/// Get chat history for a session, windowed by an optional before/after
+/// message-id cursor and limit, instead of dumping the whole session
 pub async fn get_chat_history(
+    State(state): State<AppState>,
     Path(session_id): Path<String>,
-) -> Result<Json<ApiResponse<ChatSession>>, StatusCode> {
-    let sessions = CHAT_SESSIONS.read().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    match sessions.get(&session_id) {
-        Some(session) => Ok(Json(ApiResponse {
+    Query(params): Query<HistoryRangeQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<ChatMessage>>>, StatusCode> {
+    match state
+        .storage
+        .history_page(&session_id, params.before.as_deref(), params.after.as_deref(), params.limit)
+        .await
+    {
+        HistoryPage::Ok {
+            messages,
+            next_cursor,
+            prev_cursor,
+        } => Ok(Json(ApiResponse {
             success: true,
-            data: Some(session.clone()),
-            message: format!("Retrieved {} messages", session.messages.len()),
+            message: format!("Retrieved {} messages", messages.len()),
+            data: Some(PaginatedResponse {
+                items: messages,
+                next_cursor,
+                prev_cursor,
+            }),
         })),
-        None => Err(StatusCode::NOT_FOUND),
+        HistoryPage::SessionNotFound => Err(StatusCode::NOT_FOUND),
+        HistoryPage::AnchorNotFound => Err(StatusCode::UNPROCESSABLE_ENTITY),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LatestQuery {
+    #[serde(default = "default_history_count")]
+    pub n: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnchoredQuery {
+    pub msg_id: String,
+    #[serde(default = "default_history_count")]
+    pub n: usize,
+}
+
+fn default_history_count() -> usize {
+    50
+}
+
+async fn history_page_response(
+    state: &AppState,
+    session_id: &str,
+    before: Option<&str>,
+    after: Option<&str>,
+    limit: usize,
+) -> Result<Json<ApiResponse<PaginatedResponse<ChatMessage>>>, StatusCode> {
+    match state.storage.history_page(session_id, before, after, limit).await {
+        HistoryPage::Ok {
+            messages,
+            next_cursor,
+            prev_cursor,
+        } => Ok(Json(ApiResponse {
+            success: true,
+            message: format!("Retrieved {} messages", messages.len()),
+            data: Some(PaginatedResponse {
+                items: messages,
+                next_cursor,
+                prev_cursor,
+            }),
+        })),
+        HistoryPage::SessionNotFound => Err(StatusCode::NOT_FOUND),
+        HistoryPage::AnchorNotFound => Err(StatusCode::UNPROCESSABLE_ENTITY),
     }
 }
 
+/// Get the most recent `n` messages in a session
+pub async fn get_chat_latest(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(params): Query<LatestQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<ChatMessage>>>, StatusCode> {
+    history_page_response(&state, &session_id, None, None, params.n).await
+}
+
+/// Get up to `n` messages before a given message id
+pub async fn get_chat_before(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(params): Query<AnchoredQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<ChatMessage>>>, StatusCode> {
+    history_page_response(&state, &session_id, Some(&params.msg_id), None, params.n).await
+}
+
+/// Get up to `n` messages after a given message id
+pub async fn get_chat_after(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(params): Query<AnchoredQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<ChatMessage>>>, StatusCode> {
+    history_page_response(&state, &session_id, None, Some(&params.msg_id), params.n).await
+}
+
 /// Upload and process a document
 pub async fn upload_document(
     State(state): State<AppState>,
@@ -600,8 +1030,9 @@ pub async fn upload_document(
     };
     
     // Add to memory as an experience
+    let embedding = state.ollama.embed(&text).await.ok();
+    let exp = Experience::new(&text, &format!("document:{}", payload.filename)).with_embedding(embedding);
     let mut mem = state.memory.write().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let exp = Experience::new(&text, &format!("document:{}", payload.filename));
     mem.remember(exp);
     
     Ok(Json(ApiResponse {
@@ -615,27 +1046,110 @@ pub async fn upload_document(
     }))
 }
 
+/// Upload a raw file via multipart/form-data. Unlike `upload_document`, the
+/// MIME type is sniffed from magic bytes/extension rather than trusted from a
+/// caller-supplied string, and the file arrives as raw bytes in the `file`
+/// field rather than inside a JSON/base64 payload. The field is read whole,
+/// so the entire file is held in memory before processing.
+pub async fn upload_document_multipart(
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<Json<ApiResponse<DocumentUploadResponse>>, StatusCode> {
+    let mut filename = "upload".to_string();
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut found_file = false;
+
+    while let Some(field) = multipart.next_field().await.map_err(|_| StatusCode::BAD_REQUEST)? {
+        if field.name() == Some("file") {
+            filename = field.file_name().unwrap_or("upload").to_string();
+            bytes = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?.to_vec();
+            found_file = true;
+        }
+    }
+
+    if !found_file {
+        return Ok(Json(ApiResponse {
+            success: false,
+            data: None,
+            message: "No 'file' field found in multipart upload".to_string(),
+        }));
+    }
+
+    let mime_type = DocumentProcessor::sniff_mime_type(&bytes, &filename);
+    let byte_size = bytes.len();
+    let processor = DocumentProcessor::new();
+
+    let text = match processor.process_document_bytes(&bytes, &mime_type) {
+        Ok(text) => text,
+        Err(e) => return Ok(Json(ApiResponse {
+            success: false,
+            data: None,
+            message: format!("Failed to process document: {}", e),
+        })),
+    };
+
+    let metadata = serde_json::json!({
+        "filename": filename,
+        "mime_type": mime_type,
+        "byte_size": byte_size,
+    })
+    .to_string();
+
+    let embedding = state.ollama.embed(&text).await.ok();
+    let exp = Experience::with_metadata(&text, &format!("document:{}", filename), metadata)
+        .with_embedding(embedding);
+    let mut mem = state.memory.write().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    mem.remember(exp);
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(DocumentUploadResponse {
+            processed: true,
+            text,
+            added_to_memory: true,
+        }),
+        message: format!("Document '{}' ({}, {} bytes) processed and added to memory", filename, mime_type, byte_size),
+    }))
+}
+
 /// Export chat session
 pub async fn export_chat_session(
+    State(state): State<AppState>,
     Query(params): Query<ExportRequest>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    let sessions = CHAT_SESSIONS.read().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    let session = match sessions.get(&params.session_id) {
+    let session = match state.storage.get_session(&params.session_id).await {
         Some(s) => s,
         None => return Err(StatusCode::NOT_FOUND),
     };
-    
+
     let exporter = ChatExporter::new();
     let exported = match params.format.as_str() {
-        "json" => exporter.export_json(session),
-        "txt" => exporter.export_txt(session),
-        "markdown" | "md" => exporter.export_markdown(session),
-        "html" => exporter.export_html(session),
+        "json" => exporter.export_json(&session),
+        "txt" => exporter.export_txt(&session),
+        "markdown" | "md" => exporter.export_markdown(&session),
+        "html" => exporter.export_html(&session),
+        "encrypted" => {
+            let key = match state.config.export_key.as_ref() {
+                Some(k) => k,
+                None => return Ok(Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    message: "EXPORT_KEY is not configured; cannot encrypt export".to_string(),
+                })),
+            };
+            match exporter.export_encrypted(&session, key.as_bytes()) {
+                Ok(envelope) => envelope,
+                Err(e) => return Ok(Json(ApiResponse {
+                    success: false,
+                    data: None,
+                    message: format!("Failed to encrypt export: {}", e),
+                })),
+            }
+        }
         _ => return Ok(Json(ApiResponse {
             success: false,
             data: None,
-            message: format!("Unsupported format: {}. Use json, txt, markdown, or html", params.format),
+            message: format!("Unsupported format: {}. Use json, txt, markdown, html, or encrypted", params.format),
         })),
     };
     
@@ -653,12 +1167,15 @@ pub async fn execute_http_request(
 ) -> Result<Json<ApiResponse<HttpRequestResponse>>, StatusCode> {
     let processor = ChatProcessor::new();
     
-    match processor.execute_http_request(
-        &payload.method,
-        &payload.url,
-        payload.body.clone(),
-        payload.headers.clone(),
-    ) {
+    match processor
+        .execute_http_request(
+            &payload.method,
+            &payload.url,
+            payload.body.clone(),
+            payload.headers.clone(),
+        )
+        .await
+    {
         Ok(response) => {
             let mut learning_record_id = None;
             
@@ -673,20 +1190,21 @@ pub async fn execute_http_request(
                 );
                 
                 learning_record_id = Some(record.id.clone());
-                
+
                 // Store in learning records
-                let mut records = API_LEARNING_RECORDS.write()
-                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-                records.insert(record.id.clone(), record.clone());
-                
+                state.storage.put_record(record.clone()).await;
+
                 // Also add to memory as experience
-                let mut mem = state.memory.write()
-                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+                let content = format!("API Call: {} {} - Status {}", payload.method, payload.url, response.status);
+                let embedding = state.ollama.embed(&content).await.ok();
                 let exp = Experience::with_metadata(
-                    &format!("API Call: {} {} - Status {}", payload.method, payload.url, response.status),
+                    &content,
                     "api_learning",
                     format!("record_id:{}", record.id),
-                );
+                )
+                .with_embedding(embedding);
+                let mut mem = state.memory.write()
+                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
                 mem.remember(exp);
             }
             
@@ -715,31 +1233,52 @@ pub async fn execute_http_request(
 }
 
 /// Get all API learning records
-pub async fn get_learning_records() -> Result<Json<ApiResponse<Vec<crate::chat::ApiLearningRecord>>>, StatusCode> {
-    let records = API_LEARNING_RECORDS.read()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    let mut records_vec: Vec<crate::chat::ApiLearningRecord> = records.values().cloned().collect();
-    records_vec.sort_by(|a, b| b.learned_at.cmp(&a.learned_at));
-    
+pub async fn get_learning_records(
+    State(state): State<AppState>,
+    Query(params): Query<RecordListQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<crate::chat::ApiLearningRecord>>>, StatusCode> {
+    let created_after = match params.created_after.as_deref() {
+        Some(ts) => Some(
+            chrono::DateTime::parse_from_rfc3339(ts)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?,
+        ),
+        None => None,
+    };
+
+    let (items, next_cursor, prev_cursor) = state
+        .storage
+        .list_records_page(
+            params.before.as_deref(),
+            params.after.as_deref(),
+            params.limit,
+            params.tag.as_deref(),
+            created_after,
+            matches!(params.sort, SortOrder::Desc),
+        )
+        .await
+        .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+
     Ok(Json(ApiResponse {
         success: true,
-        data: Some(records_vec.clone()),
-        message: format!("Retrieved {} learning records", records_vec.len()),
+        message: format!("Retrieved {} learning records", items.len()),
+        data: Some(PaginatedResponse {
+            items,
+            next_cursor,
+            prev_cursor,
+        }),
     }))
 }
 
 /// Get learning record by ID
 pub async fn get_learning_record_by_id(
+    State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<ApiResponse<crate::chat::ApiLearningRecord>>, StatusCode> {
-    let records = API_LEARNING_RECORDS.read()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    match records.get(&id) {
+    match state.storage.get_record(&id).await {
         Some(record) => Ok(Json(ApiResponse {
             success: true,
-            data: Some(record.clone()),
+            data: Some(record),
             message: "Learning record found".to_string(),
         })),
         None => Err(StatusCode::NOT_FOUND),
@@ -748,114 +1287,282 @@ pub async fn get_learning_record_by_id(
 
 /// Update learning record (tags and summary)
 pub async fn update_learning_record(
+    State(state): State<AppState>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateLearningRecordRequest>,
 ) -> Result<Json<ApiResponse<crate::chat::ApiLearningRecord>>, StatusCode> {
-    let mut records = API_LEARNING_RECORDS.write()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    match records.get_mut(&id) {
-        Some(record) => {
-            if let Some(tags) = payload.tags {
-                record.tags = tags;
-            }
-            if let Some(summary) = payload.summary {
-                record.summary = summary;
-            }
-            
-            Ok(Json(ApiResponse {
-                success: true,
-                data: Some(record.clone()),
-                message: "Learning record updated".to_string(),
-            }))
-        }
-        None => Err(StatusCode::NOT_FOUND),
+    let mut record = match state.storage.get_record(&id).await {
+        Some(record) => record,
+        None => return Err(StatusCode::NOT_FOUND),
+    };
+    if let Some(tags) = payload.tags {
+        record.tags = tags;
     }
+    if let Some(summary) = payload.summary {
+        record.summary = summary;
+    }
+    state.storage.put_record(record.clone()).await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(record),
+        message: "Learning record updated".to_string(),
+    }))
 }
 
-/// Delete learning record
+/// Soft-delete a learning record: the current version is tombstoned rather
+/// than erased, so `restore_learning_record` can bring it back
 pub async fn delete_learning_record(
+    State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    let mut records = API_LEARNING_RECORDS.write()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    match records.remove(&id) {
-        Some(_) => Ok(Json(ApiResponse {
+    if state.storage.delete_record(&id).await {
+        Ok(Json(ApiResponse {
             success: true,
             data: Some("Learning record deleted".to_string()),
             message: format!("Record {} has been deleted", id),
+        }))
+    } else {
+        Err(StatusCode::NOT_FOUND)
+    }
+}
+
+/// List a learning record's full version history, oldest first, including
+/// any tombstones left by prior deletes
+pub async fn get_learning_record_versions(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<crate::learning_store::RecordVersion>>>, StatusCode> {
+    match state.storage.list_versions(&id).await {
+        Some(versions) => Ok(Json(ApiResponse {
+            success: true,
+            message: format!("Found {} version(s)", versions.len()),
+            data: Some(versions),
+        })),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Reinstate the last non-deleted version of a tombstoned learning record
+pub async fn restore_learning_record(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<crate::chat::ApiLearningRecord>>, StatusCode> {
+    match state.storage.restore_record(&id).await {
+        Some(record) => Ok(Json(ApiResponse {
+            success: true,
+            message: format!("Record {} has been restored", id),
+            data: Some(record),
         })),
         None => Err(StatusCode::NOT_FOUND),
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ReplayLearningRecordRequest {
+    #[serde(default)]
+    pub assertions: Vec<crate::chat::ReplayAssertion>,
+}
+
+/// Re-issue a learning record's recorded HTTP request and check the fresh
+/// response against assertions (or the original status code, if none given)
+pub async fn replay_learning_record(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<ReplayLearningRecordRequest>,
+) -> Result<Json<ApiResponse<crate::chat::ReplayResult>>, StatusCode> {
+    let record = match state.storage.get_record(&id).await {
+        Some(record) => record,
+        None => return Err(StatusCode::NOT_FOUND),
+    };
+
+    let engine = crate::chat::ReplayEngine::new();
+    match engine.replay(&record, &payload.assertions).await {
+        Ok(result) => {
+            let message = if result.passed {
+                "Replay passed all assertions".to_string()
+            } else {
+                format!("Replay failed {} assertion(s)", result.failures.len())
+            };
+            Ok(Json(ApiResponse {
+                success: result.passed,
+                message,
+                data: Some(result),
+            }))
+        }
+        Err(e) => Ok(Json(ApiResponse {
+            success: false,
+            data: None,
+            message: format!("Replay failed: {}", e),
+        })),
+    }
+}
+
 /// Search learning records by tag or URL
+#[derive(Debug, Serialize)]
+pub struct LearningRecordSearchResult {
+    pub record: crate::chat::ApiLearningRecord,
+    pub score: f32,
+}
+
+/// Search learning records with a BM25-ranked inverted index over
+/// `url`/`tags`/`summary` (typo-tolerant, with `tags` weighted above
+/// `summary` above `url`), instead of a linear substring scan
 pub async fn search_learning_records(
-    Query(params): Query<SearchQuery>,
-) -> Result<Json<ApiResponse<Vec<crate::chat::ApiLearningRecord>>>, StatusCode> {
-    let records = API_LEARNING_RECORDS.read()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    let query_lower = params.q.to_lowercase();
-    let results: Vec<crate::chat::ApiLearningRecord> = records
-        .values()
-        .filter(|r| {
-            r.url.to_lowercase().contains(&query_lower)
-                || r.tags.iter().any(|t| t.to_lowercase().contains(&query_lower))
-                || r.summary.to_lowercase().contains(&query_lower)
-        })
-        .cloned()
+    State(state): State<AppState>,
+    Query(params): Query<SearchPageQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<LearningRecordSearchResult>>>, StatusCode> {
+    let (matches, next_cursor, prev_cursor) = state
+        .storage
+        .search_records_page(
+            &params.q,
+            params.before.as_deref(),
+            params.after.as_deref(),
+            params.limit,
+        )
+        .await
+        .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+
+    let items: Vec<LearningRecordSearchResult> = matches
+        .into_iter()
+        .map(|(record, score)| LearningRecordSearchResult { record, score })
         .collect();
-    
+
     Ok(Json(ApiResponse {
         success: true,
-        data: Some(results.clone()),
-        message: format!("Found {} matching records", results.len()),
+        message: format!("Found {} matching records", items.len()),
+        data: Some(PaginatedResponse {
+            items,
+            next_cursor,
+            prev_cursor,
+        }),
+    }))
+}
+
+/// Search learning records by embedding similarity instead of keyword
+/// overlap, so a query can surface conceptually related records that share
+/// no exact words with their `summary`
+pub async fn search_learning_records_semantic(
+    State(state): State<AppState>,
+    Query(params): Query<SearchQuery>,
+) -> Result<Json<ApiResponse<Vec<LearningRecordSearchResult>>>, StatusCode> {
+    let results = state
+        .storage
+        .search_semantic(&params.q, 20, state.config.semantic_search_threshold)
+        .await
+        .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?
+        .into_iter()
+        .map(|(record, score)| LearningRecordSearchResult { record, score })
+        .collect::<Vec<_>>();
+
+    Ok(Json(ApiResponse {
+        success: true,
+        message: format!("Found {} semantically matching records", results.len()),
+        data: Some(results),
     }))
 }
 
 /// Clear all learning records
-pub async fn clear_learning_records() -> Result<Json<ApiResponse<String>>, StatusCode> {
-    let mut records = API_LEARNING_RECORDS.write()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    let count = records.len();
-    records.clear();
-    
+pub async fn clear_learning_records(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let count = state.storage.clear_records().await;
+
     Ok(Json(ApiResponse {
         success: true,
-        data: Some(format!("Cleared {} learning records", count)),
-        message: "All learning records deleted".to_string(),
+        data: Some(format!("Tombstoned {} learning records", count)),
+        message: "All learning records deleted (recoverable via restore)".to_string(),
+    }))
+}
+
+/// Permanently erase every version of every learning record. Unlike
+/// `clear_learning_records`, tombstoned data is gone for good — admin use only.
+pub async fn purge_learning_records(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    let count = state.storage.purge_records().await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Some(format!("Purged {} learning records", count)),
+        message: "All learning record history has been permanently erased".to_string(),
     }))
 }
 
 /// Get all chat sessions (list)
 pub async fn list_chat_sessions(
-) -> Result<Json<ApiResponse<Vec<String>>>, StatusCode> {
-    let sessions = CHAT_SESSIONS.read().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    let session_ids: Vec<String> = sessions.keys().cloned().collect();
-    
+    State(state): State<AppState>,
+    Query(params): Query<SessionListQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<String>>>, StatusCode> {
+    let (items, next_cursor, prev_cursor) = state
+        .storage
+        .list_sessions_page(params.before.as_deref(), params.after.as_deref(), params.limit)
+        .await
+        .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+
     Ok(Json(ApiResponse {
         success: true,
-        data: Some(session_ids.clone()),
-        message: format!("Found {} active chat sessions", session_ids.len()),
+        message: format!("Found {} active chat sessions", items.len()),
+        data: Some(PaginatedResponse {
+            items,
+            next_cursor,
+            prev_cursor,
+        }),
     }))
 }
 
 /// Clear a specific chat session
 pub async fn clear_chat_session(
+    State(state): State<AppState>,
     Path(session_id): Path<String>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    let mut sessions = CHAT_SESSIONS.write().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    match sessions.remove(&session_id) {
-        Some(_) => Ok(Json(ApiResponse {
+    if state.storage.delete_session(&session_id).await {
+        state.realtime.close_session(&session_id);
+        Ok(Json(ApiResponse {
             success: true,
             data: Some("Session cleared".to_string()),
             message: format!("Chat session {} has been deleted", session_id),
-        })),
-        None => Err(StatusCode::NOT_FOUND),
+        }))
+    } else {
+        Err(StatusCode::NOT_FOUND)
     }
 }
+
+/// Upgrade to a WebSocket subscribed to `session_id`'s live message feed.
+/// Every message added to the session via any of the `chat/send*` endpoints
+/// is pushed to connected clients, and a "session closed" frame is sent if
+/// the session is deleted while this connection is subscribed.
+pub async fn chat_session_ws(
+    ws: WebSocketUpgrade,
+    Path(session_id): Path<String>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_chat_session_socket(socket, session_id, state))
+}
+
+async fn handle_chat_session_socket(socket: WebSocket, session_id: String, state: AppState) {
+    use axum::extract::ws::Message;
+    use futures_util::{SinkExt, StreamExt};
+
+    let (mut sink, mut stream) = socket.split();
+    let (connection_id, mut outbound) = state.realtime.subscribe(&session_id);
+
+    let forward_task = tokio::spawn(async move {
+        while let Some(message) = outbound.recv().await {
+            if sink.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    while let Some(Ok(message)) = stream.next().await {
+        match message {
+            Message::Pong(_) => state.realtime.record_pong(&session_id, &connection_id),
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+
+    forward_task.abort();
+    state.realtime.unsubscribe(&session_id, &connection_id);
+}
@@ -1,8 +1,10 @@
 use crate::{
     experience::Experience,
+    llm::{ConversationTurn, LlmProvider},
     pattern::PatternRecognizer,
 };
 use serde::{Deserialize, Serialize};
+use std::io::Read;
 
 /// Chat message structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +48,17 @@ impl ChatMessage {
     }
 }
 
+/// Convert persisted session turns into the role-tagged list an `LlmProvider` expects
+fn to_conversation_turns(history: &[ChatMessage]) -> Vec<ConversationTurn> {
+    history
+        .iter()
+        .map(|m| ConversationTurn {
+            role: m.role.clone(),
+            content: m.content.clone(),
+        })
+        .collect()
+}
+
 /// Chat session management
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatSession {
@@ -76,44 +89,200 @@ impl ChatSession {
     }
 }
 
+/// Description of a tool the model is allowed to call, in JSON-schema style
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolSpec {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+/// A tool-call the model asked us to perform, parsed out of its reply
+#[derive(Debug, Clone, Deserialize)]
+struct ToolCall {
+    tool: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
+}
+
+/// Registry of tools available to the model during a chat turn
+#[derive(Debug, Clone)]
+pub struct ToolRegistry {
+    pub specs: Vec<ToolSpec>,
+}
+
+impl ToolRegistry {
+    /// Tools wired up by default: HTTP calls, memory search, and document processing
+    pub fn default_tools() -> Self {
+        Self {
+            specs: vec![
+                ToolSpec {
+                    name: "execute_http_request".to_string(),
+                    description: "Make an HTTP request and learn from the response".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "method": {"type": "string"},
+                            "url": {"type": "string"},
+                            "body": {"type": "string"}
+                        },
+                        "required": ["method", "url"]
+                    }),
+                },
+                ToolSpec {
+                    name: "memory.search".to_string(),
+                    description: "Search stored experiences for a keyword or phrase".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "query": {"type": "string"}
+                        },
+                        "required": ["query"]
+                    }),
+                },
+                ToolSpec {
+                    name: "document.process".to_string(),
+                    description: "Extract readable text from a document's raw content".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "content": {"type": "string"},
+                            "filetype": {"type": "string"}
+                        },
+                        "required": ["content", "filetype"]
+                    }),
+                },
+            ],
+        }
+    }
+
+    /// Render the tool specs as a block to prepend to the prompt/system message
+    pub fn system_prompt_block(&self) -> String {
+        let mut block = String::from(
+            "You have access to the following tools. To call one, reply with ONLY a JSON object \
+            of the form {\"tool\": \"<name>\", \"arguments\": {...}}. Otherwise reply with plain text.\n",
+        );
+        for spec in &self.specs {
+            block.push_str(&format!(
+                "- {}: {} | parameters: {}\n",
+                spec.name, spec.description, spec.parameters
+            ));
+        }
+        block
+    }
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self::default_tools()
+    }
+}
+
 /// Chat processor with context-aware responses
 pub struct ChatProcessor {
-    pub ollama_client: Option<std::sync::Arc<crate::ollama::OllamaClient>>,
+    pub provider: Option<std::sync::Arc<dyn LlmProvider>>,
+    pub tools: ToolRegistry,
+    pub max_tool_steps: usize,
 }
 
 impl ChatProcessor {
     pub fn new() -> Self {
         Self {
-            ollama_client: None,
+            provider: None,
+            tools: ToolRegistry::default_tools(),
+            max_tool_steps: 5,
         }
     }
 
+    /// Build a processor backed by Ollama
     pub fn with_ollama(ollama_client: std::sync::Arc<crate::ollama::OllamaClient>) -> Self {
         Self {
-            ollama_client: Some(ollama_client),
+            provider: Some(ollama_client as std::sync::Arc<dyn LlmProvider>),
+            tools: ToolRegistry::default_tools(),
+            max_tool_steps: 5,
+        }
+    }
+
+    /// Build a processor backed by an arbitrary `LlmProvider` (e.g. OpenAI-compatible)
+    pub fn with_provider(provider: std::sync::Arc<dyn LlmProvider>) -> Self {
+        Self {
+            provider: Some(provider),
+            tools: ToolRegistry::default_tools(),
+            max_tool_steps: 5,
+        }
+    }
+
+    /// Try to parse a tool-call JSON block out of the model's reply
+    fn parse_tool_call(reply: &str) -> Option<ToolCall> {
+        let trimmed = reply.trim();
+        let start = trimmed.find('{')?;
+        let end = trimmed.rfind('}')?;
+        if end <= start {
+            return None;
+        }
+        serde_json::from_str::<ToolCall>(&trimmed[start..=end]).ok()
+    }
+
+    /// Dispatch a parsed tool-call to its implementation. Results are not cached:
+    /// each call runs the tool again.
+    async fn dispatch_tool(
+        &self,
+        call: &ToolCall,
+        memory: &crate::memory::Memory,
+    ) -> Result<String, String> {
+        match call.tool.as_str() {
+            "execute_http_request" => {
+                let method = call.arguments["method"].as_str().unwrap_or("GET");
+                let url = call.arguments["url"]
+                    .as_str()
+                    .ok_or_else(|| "missing 'url' argument".to_string())?;
+                let body = call.arguments["body"].as_str().map(|s| s.to_string());
+                let response = self.execute_http_request(method, url, body, None).await?;
+                Ok(response.body)
+            }
+            "memory.search" => {
+                let query = call.arguments["query"]
+                    .as_str()
+                    .ok_or_else(|| "missing 'query' argument".to_string())?;
+                let results = memory.search(query);
+                if results.is_empty() {
+                    Ok("No matching experiences found.".to_string())
+                } else {
+                    Ok(results
+                        .iter()
+                        .map(|e| format!("- {} (from {})", e.content, e.source))
+                        .collect::<Vec<_>>()
+                        .join("\n"))
+                }
+            }
+            "document.process" => {
+                let content = call.arguments["content"]
+                    .as_str()
+                    .ok_or_else(|| "missing 'content' argument".to_string())?;
+                let filetype = call.arguments["filetype"].as_str().unwrap_or("txt");
+                DocumentProcessor::new().process_document(content, filetype)
+            }
+            other => Err(format!("Unknown tool: {}", other)),
         }
     }
 
-    /// Generate response based on user input and memory context
+    /// Generate response based on user input and memory context, dispatching tool calls as needed.
+    /// `history` is the prior turns of this chat session (oldest first), assembled into the
+    /// message list sent to the provider so multi-turn context is preserved.
     pub async fn process_message(
         &self,
         user_input: &str,
         memory: &crate::memory::Memory,
         patterns: &mut PatternRecognizer,
+        history: &[ChatMessage],
     ) -> ChatMessage {
-        // Analyze patterns from user input
-        let keywords = Self::extract_keywords(user_input);
-        
-        // Search for relevant experiences
-        let mut relevant_experiences: Vec<&Experience> = Vec::new();
-        for keyword in &keywords {
-            let results = memory.search(keyword);
-            relevant_experiences.extend(results);
-        }
-
-        // Remove duplicates
-        relevant_experiences.sort_by_key(|e| &e.id);
-        relevant_experiences.dedup_by_key(|e| &e.id);
+        // Search for relevant experiences, ranked by BM25 relevance rather than
+        // keyword substring matching
+        let relevant_experiences: Vec<&Experience> = memory
+            .search_bm25(user_input, 10)
+            .into_iter()
+            .map(|(exp, _score)| exp)
+            .collect();
 
         // Build context
         let context_ids: Vec<String> = relevant_experiences
@@ -122,27 +291,65 @@ impl ChatProcessor {
             .collect();
 
         // Generate response
-        let response_content = if let Some(ref ollama) = self.ollama_client {
-            // Use Ollama for AI-powered responses
-            if ollama.is_enabled() {
-                let context_texts: Vec<String> = relevant_experiences
-                    .iter()
-                    .map(|e| format!("- {} (from {})", e.content, e.source))
-                    .collect();
-
-                match ollama.generate_with_context(user_input, &context_texts).await {
-                    Ok(ai_response) => ai_response,
-                    Err(e) => {
-                        tracing::warn!("Ollama generation failed: {}. Using fallback.", e);
-                        if relevant_experiences.is_empty() {
-                            Self::generate_default_response(user_input)
-                        } else {
-                            Self::generate_context_aware_response(user_input, &relevant_experiences, patterns)
+        let response_content = if let Some(ref provider) = self.provider {
+            // Use the configured LLM provider for AI-powered responses
+            if provider.is_enabled() {
+                let mut context_texts: Vec<String> = vec![self.tools.system_prompt_block()];
+                context_texts.extend(
+                    relevant_experiences
+                        .iter()
+                        .map(|e| format!("- {} (from {})", e.content, e.source)),
+                );
+
+                let history_turns = to_conversation_turns(history);
+
+                // Tool-calling loop: keep re-querying until the model returns plain text,
+                // for at most `max_tool_steps` queries; after that the fallback response is used
+                let mut working_input = user_input.to_string();
+                let mut final_response = None;
+                for step in 0..self.max_tool_steps {
+                    match provider
+                        .generate_with_context(&working_input, &context_texts, &history_turns)
+                        .await
+                    {
+                        Ok(ai_response) => match Self::parse_tool_call(&ai_response) {
+                            Some(call) => {
+                                let tool_result = self
+                                    .dispatch_tool(&call, memory)
+                                    .await
+                                    .unwrap_or_else(|e| format!("Tool error: {}", e));
+                                tracing::debug!(
+                                    "Tool step {}: {} -> {}",
+                                    step,
+                                    call.tool,
+                                    tool_result
+                                );
+                                working_input = format!(
+                                    "{}\n\n[tool:{} result]\n{}",
+                                    working_input, call.tool, tool_result
+                                );
+                            }
+                            None => {
+                                final_response = Some(ai_response);
+                                break;
+                            }
+                        },
+                        Err(e) => {
+                            tracing::warn!("LLM generation failed: {}. Using fallback.", e);
+                            break;
                         }
                     }
                 }
+
+                final_response.unwrap_or_else(|| {
+                    if relevant_experiences.is_empty() {
+                        Self::generate_default_response(user_input)
+                    } else {
+                        Self::generate_context_aware_response(user_input, &relevant_experiences, patterns)
+                    }
+                })
             } else {
-                // Ollama disabled, use fallback
+                // Provider disabled, use fallback
                 if relevant_experiences.is_empty() {
                     Self::generate_default_response(user_input)
                 } else {
@@ -150,7 +357,7 @@ impl ChatProcessor {
                 }
             }
         } else {
-            // No Ollama client, use fallback
+            // No LLM provider configured, use fallback
             if relevant_experiences.is_empty() {
                 Self::generate_default_response(user_input)
             } else {
@@ -161,20 +368,45 @@ impl ChatProcessor {
         ChatMessage::with_context("assistant", response_content, context_ids)
     }
 
-    /// Extract keywords from user input
-    fn extract_keywords(input: &str) -> Vec<String> {
-        input
-            .to_lowercase()
-            .split_whitespace()
-            .filter(|word| word.len() > 2) // Filter short words
-            .map(|word| {
-                // Remove punctuation
-                word.chars()
-                    .filter(|c| c.is_alphanumeric())
-                    .collect::<String>()
-            })
-            .filter(|word| !word.is_empty())
-            .collect()
+    /// Generate a response as a stream of incremental text chunks instead of blocking
+    /// on the full completion. Returns the context IDs used (for building the final
+    /// `ChatMessage` once the stream is exhausted) alongside the token stream itself.
+    pub async fn process_message_stream(
+        &self,
+        user_input: &str,
+        memory: &crate::memory::Memory,
+        history: &[ChatMessage],
+    ) -> Result<
+        (
+            Vec<String>,
+            std::pin::Pin<Box<dyn futures_util::Stream<Item = Result<String, String>> + Send>>,
+        ),
+        String,
+    > {
+        let provider = self
+            .provider
+            .as_ref()
+            .filter(|p| p.is_enabled())
+            .ok_or_else(|| "Streaming requires a configured LLM provider".to_string())?;
+
+        let relevant_experiences: Vec<&Experience> = memory
+            .search_bm25(user_input, 10)
+            .into_iter()
+            .map(|(exp, _score)| exp)
+            .collect();
+
+        let context_ids: Vec<String> = relevant_experiences.iter().map(|e| e.id.clone()).collect();
+        let context_texts: Vec<String> = relevant_experiences
+            .iter()
+            .map(|e| format!("- {} (from {})", e.content, e.source))
+            .collect();
+
+        let history_turns = to_conversation_turns(history);
+        let token_stream = provider
+            .generate_with_context_stream(user_input, &context_texts, &history_turns)
+            .await?;
+
+        Ok((context_ids, token_stream))
     }
 
     /// Generate default response when no context found
@@ -209,8 +441,8 @@ impl ChatProcessor {
             patterns.analyze(exp);
         }
 
-        let top_patterns = patterns.get_top_patterns(5);
-        
+        let top_entities = patterns.get_top_entities(5);
+
         let mut response = format!(
             "Berdasarkan {} pengalaman relevan yang saya temukan:\n\n",
             experiences.len()
@@ -219,27 +451,36 @@ impl ChatProcessor {
         // Add most relevant experiences
         let max_experiences = 3.min(experiences.len());
         for (i, exp) in experiences.iter().take(max_experiences).enumerate() {
-            response.push_str(&format!("{}. {} (dari {})\n", i + 1, exp.content, exp.source));
+            let sentiment = patterns
+                .sentiment_of(&exp.id)
+                .map(|s| format!(" [sentiment: {:+.2}]", s))
+                .unwrap_or_default();
+            response.push_str(&format!("{}. {} (dari {}){}\n", i + 1, exp.content, exp.source, sentiment));
         }
 
-        // Add pattern insights
-        if !top_patterns.is_empty() {
-            response.push_str("\n🔍 Pola yang terdeteksi: ");
-            let pattern_names: Vec<String> = top_patterns
+        // Add detected entities instead of raw keywords
+        if !top_entities.is_empty() {
+            response.push_str("\n🔍 Entitas yang terdeteksi: ");
+            let entity_labels: Vec<String> = top_entities
                 .iter()
-                .take(3)
-                .map(|p| p.keyword.clone())
+                .map(|e| format!("{} ({:?})", e.text, e.entity_type))
                 .collect();
-            response.push_str(&pattern_names.join(", "));
+            response.push_str(&entity_labels.join(", "));
         }
 
+        let avg_sentiment = patterns.average_sentiment_magnitude();
+        response.push_str(&format!(
+            "\n📊 Rata-rata intensitas sentimen: {:.2}",
+            avg_sentiment
+        ));
+
         response.push_str("\n\nApakah ini menjawab pertanyaan Anda?");
-        
+
         response
     }
 
-    /// Execute HTTP request and learn from response
-    pub fn execute_http_request(
+    /// Execute an HTTP request and learn from the response
+    pub async fn execute_http_request(
         &self,
         method: &str,
         url: &str,
@@ -251,15 +492,47 @@ impl ChatProcessor {
             return Err("URL must start with http:// or https://".to_string());
         }
 
-        // For now, return simulated response
-        // In production, use reqwest to make actual HTTP calls
+        let method = reqwest::Method::from_bytes(method.to_uppercase().as_bytes())
+            .map_err(|_| format!("Invalid HTTP method: {}", method))?;
+
+        let client = reqwest::Client::new();
+        let mut request = client.request(method, url);
+        if let Some(headers) = &headers {
+            for (key, value) in headers {
+                request = request.header(key, value);
+            }
+        }
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("HTTP request failed: {}", e))?;
+
+        let status = response.status().as_u16();
+        let success = response.status().is_success();
+        let response_headers: Vec<(String, String)> = response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    value.to_str().unwrap_or_default().to_string(),
+                )
+            })
+            .collect();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read response body: {}", e))?;
+
         Ok(HttpResponse {
-            status: 200,
-            body: format!(
-                "Simulated {} request to {}\nHeaders: {:?}\nBody: {:?}\n\nNote: In production, this will make actual HTTP requests using reqwest.",
-                method, url, headers, body
-            ),
-            success: true,
+            status,
+            headers: response_headers,
+            body,
+            success,
         })
     }
 }
@@ -268,6 +541,7 @@ impl ChatProcessor {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpResponse {
     pub status: u16,
+    pub headers: Vec<(String, String)>,
     pub body: String,
     pub success: bool,
 }
@@ -341,6 +615,217 @@ impl ApiLearningRecord {
     }
 }
 
+/// What part of a replayed response an assertion inspects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReplayTarget {
+    Status,
+    Header { name: String },
+    /// A small JSONPath-like selector into the response body, e.g.
+    /// `$.data.items[0].status` or `data.items.0.status`
+    Json { selector: String },
+}
+
+/// How an assertion's extracted value must compare
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum ReplayPredicate {
+    Equals(String),
+    Contains(String),
+    GreaterThan(f64),
+    /// Regex pattern the extracted value must match
+    Matches(String),
+    Exists,
+}
+
+/// An assertion checked against a replayed `execute_http_request` response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayAssertion {
+    pub target: ReplayTarget,
+    pub predicate: ReplayPredicate,
+}
+
+/// Outcome of replaying a single learning record against its assertions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayResult {
+    pub record_id: String,
+    pub passed: bool,
+    pub actual_status: u16,
+    pub actual_body: String,
+    pub failures: Vec<String>,
+    /// Values extracted per assertion target, keyed by a human-readable label
+    /// (`"status"`, `"header:X-Name"`, `"json:$.path"`), for inspecting drift
+    pub extracted_values: std::collections::HashMap<String, String>,
+}
+
+/// Re-issues a recorded `execute_http_request` call and checks the fresh
+/// response against assertions, to catch API drift in learning records.
+pub struct ReplayEngine;
+
+impl ReplayEngine {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Label identifying an assertion's target, used as the key in
+    /// `ReplayResult::extracted_values` and in failure messages
+    fn target_label(target: &ReplayTarget) -> String {
+        match target {
+            ReplayTarget::Status => "status".to_string(),
+            ReplayTarget::Header { name } => format!("header:{}", name),
+            ReplayTarget::Json { selector } => format!("json:{}", selector),
+        }
+    }
+
+    /// Extract the string value `target` points at from a fresh response
+    fn extract_target(response: &HttpResponse, target: &ReplayTarget) -> Option<String> {
+        match target {
+            ReplayTarget::Status => Some(response.status.to_string()),
+            ReplayTarget::Header { name } => response
+                .headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone()),
+            ReplayTarget::Json { selector } => {
+                let value: serde_json::Value = serde_json::from_str(&response.body).ok()?;
+                select_json(&value, selector).map(|v| match v {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+            }
+        }
+    }
+
+    /// Check an extracted value against `predicate`, returning an error
+    /// message describing the mismatch on failure
+    fn check_predicate(actual: &Option<String>, predicate: &ReplayPredicate) -> Result<(), String> {
+        match predicate {
+            ReplayPredicate::Exists => {
+                if actual.is_none() {
+                    return Err("expected a value to be present".to_string());
+                }
+            }
+            ReplayPredicate::Equals(expected) => match actual {
+                Some(value) if value == expected => {}
+                Some(value) => return Err(format!("expected {:?}, got {:?}", expected, value)),
+                None => return Err(format!("expected {:?}, got nothing", expected)),
+            },
+            ReplayPredicate::Contains(needle) => match actual {
+                Some(value) if value.contains(needle.as_str()) => {}
+                Some(value) => return Err(format!("{:?} did not contain {:?}", value, needle)),
+                None => return Err(format!("nothing to search for {:?} in", needle)),
+            },
+            ReplayPredicate::GreaterThan(threshold) => {
+                match actual.as_deref().and_then(|v| v.parse::<f64>().ok()) {
+                    Some(n) if n > *threshold => {}
+                    Some(n) => return Err(format!("{} is not greater than {}", n, threshold)),
+                    None => return Err("value was missing or not numeric".to_string()),
+                }
+            }
+            ReplayPredicate::Matches(pattern) => {
+                let re = regex::Regex::new(pattern)
+                    .map_err(|e| format!("invalid regex {:?}: {}", pattern, e))?;
+                match actual {
+                    Some(value) if re.is_match(value) => {}
+                    Some(value) => return Err(format!("{:?} did not match /{}/", value, pattern)),
+                    None => return Err(format!("nothing to match /{}/ against", pattern)),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replay `record`'s request and evaluate `assertions` against the fresh response.
+    /// With no assertions given, defaults to checking the status code still
+    /// matches what was recorded.
+    pub async fn replay(
+        &self,
+        record: &ApiLearningRecord,
+        assertions: &[ReplayAssertion],
+    ) -> Result<ReplayResult, String> {
+        let processor = ChatProcessor::new();
+        let response = processor
+            .execute_http_request(&record.method, &record.url, record.request_body.clone(), None)
+            .await?;
+
+        let default_checks = [ReplayAssertion {
+            target: ReplayTarget::Status,
+            predicate: ReplayPredicate::Equals(record.status_code.to_string()),
+        }];
+        let checks: &[ReplayAssertion] = if assertions.is_empty() {
+            &default_checks
+        } else {
+            assertions
+        };
+
+        let mut failures = Vec::new();
+        let mut extracted_values = std::collections::HashMap::new();
+        for assertion in checks {
+            let label = Self::target_label(&assertion.target);
+            let actual = Self::extract_target(&response, &assertion.target);
+            if let Some(value) = &actual {
+                extracted_values.insert(label.clone(), value.clone());
+            }
+            if let Err(reason) = Self::check_predicate(&actual, &assertion.predicate) {
+                failures.push(format!("{}: {}", label, reason));
+            }
+        }
+
+        Ok(ReplayResult {
+            record_id: record.id.clone(),
+            passed: failures.is_empty(),
+            actual_status: response.status,
+            actual_body: response.body,
+            failures,
+            extracted_values,
+        })
+    }
+}
+
+/// Resolve a small JSONPath-like selector (`$.data.items[0].status` or
+/// `data.items.0.status`) against `value`. Supports dotted object keys and
+/// numeric array indices; not a full JSONPath implementation.
+fn select_json<'a>(value: &'a serde_json::Value, selector: &str) -> Option<&'a serde_json::Value> {
+    let selector = selector.strip_prefix('$').unwrap_or(selector);
+    let mut current = value;
+    for raw_segment in selector.split('.') {
+        for part in split_bracket_segments(raw_segment) {
+            if part.is_empty() {
+                continue;
+            }
+            current = match part.parse::<usize>() {
+                Ok(index) => current.as_array()?.get(index)?,
+                Err(_) => current.as_object()?.get(part)?,
+            };
+        }
+    }
+    Some(current)
+}
+
+/// Split a path segment like `items[0][1]` into `["items", "0", "1"]`
+fn split_bracket_segments(segment: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = segment;
+    match rest.find('[') {
+        Some(bracket_pos) => {
+            let (head, mut tail) = rest.split_at(bracket_pos);
+            if !head.is_empty() {
+                parts.push(head);
+            }
+            while let Some(end) = tail.find(']') {
+                parts.push(&tail[1..end]);
+                tail = &tail[end + 1..];
+            }
+            rest = tail;
+            if !rest.is_empty() {
+                parts.push(rest);
+            }
+        }
+        None => parts.push(rest),
+    }
+    parts
+}
+
 /// Document processor for file uploads
 pub struct DocumentProcessor;
 
@@ -384,6 +869,93 @@ impl DocumentProcessor {
         }
     }
 
+    /// Process raw uploaded bytes using a sniffed MIME type, extending the
+    /// string-based `process_document` path to binary formats (PDF, DOCX)
+    /// that can't be carried as a JSON string.
+    pub fn process_document_bytes(&self, bytes: &[u8], mime_type: &str) -> Result<String, String> {
+        match mime_type {
+            "application/pdf" => Self::extract_pdf_text(bytes),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
+                Self::extract_docx_text(bytes)
+            }
+            "application/json" => self.process_document(&String::from_utf8_lossy(bytes), "json"),
+            "text/csv" => self.process_document(&String::from_utf8_lossy(bytes), "csv"),
+            _ => Ok(String::from_utf8_lossy(bytes).to_string()),
+        }
+    }
+
+    /// Detect a file's MIME type from its magic bytes, falling back to
+    /// extension-based guessing, then to `text/plain` for valid UTF-8, and
+    /// finally to a generic binary type, instead of trusting a caller-supplied
+    /// `filetype` string.
+    pub fn sniff_mime_type(bytes: &[u8], filename: &str) -> String {
+        if bytes.starts_with(b"%PDF") {
+            return "application/pdf".to_string();
+        }
+        if bytes.starts_with(&[0x50, 0x4B, 0x03, 0x04]) {
+            // DOCX/XLSX/PPTX and plain ZIP share the PK\x03\x04 signature;
+            // the extension is what tells them apart.
+            return mime_guess::from_path(filename)
+                .first()
+                .map(|m| m.essence_str().to_string())
+                .unwrap_or_else(|| "application/zip".to_string());
+        }
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return "image/png".to_string();
+        }
+        if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            return "image/jpeg".to_string();
+        }
+        if let Some(guess) = mime_guess::from_path(filename).first() {
+            return guess.essence_str().to_string();
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            return "text/plain".to_string();
+        }
+        "application/octet-stream".to_string()
+    }
+
+    /// Extract plain text from a PDF's byte stream
+    fn extract_pdf_text(bytes: &[u8]) -> Result<String, String> {
+        pdf_extract::extract_text_from_mem(bytes)
+            .map_err(|e| format!("Failed to extract PDF text: {}", e))
+    }
+
+    /// Extract plain text from a DOCX's `word/document.xml` part
+    fn extract_docx_text(bytes: &[u8]) -> Result<String, String> {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+            .map_err(|e| format!("Failed to open DOCX archive: {}", e))?;
+        let mut xml = String::new();
+        archive
+            .by_name("word/document.xml")
+            .map_err(|e| format!("DOCX missing word/document.xml: {}", e))?
+            .read_to_string(&mut xml)
+            .map_err(|e| format!("Failed to read DOCX document.xml: {}", e))?;
+        Ok(Self::strip_xml_tags(&xml))
+    }
+
+    /// Strip XML tags from Word's document.xml, turning paragraph boundaries
+    /// into newlines
+    fn strip_xml_tags(xml: &str) -> String {
+        xml.split("</w:p>")
+            .map(|paragraph| {
+                let mut text = String::new();
+                let mut in_tag = false;
+                for c in paragraph.chars() {
+                    match c {
+                        '<' => in_tag = true,
+                        '>' => in_tag = false,
+                        _ if !in_tag => text.push(c),
+                        _ => {}
+                    }
+                }
+                text.trim().to_string()
+            })
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     /// Recursively extract text from JSON
     fn extract_from_json(value: &serde_json::Value) -> String {
         let mut result = String::new();
@@ -421,6 +993,16 @@ impl DocumentProcessor {
     }
 }
 
+/// Self-describing envelope for an encrypted session export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncryptedExport {
+    version: u8,
+    /// Base64-encoded 96-bit GCM nonce
+    nonce: String,
+    /// Base64-encoded ciphertext (includes the GCM authentication tag)
+    ciphertext: String,
+}
+
 /// Export functionality
 pub struct ChatExporter;
 
@@ -433,6 +1015,73 @@ impl ChatExporter {
         serde_json::to_string_pretty(session).unwrap_or_else(|e| format!("Error: {}", e))
     }
 
+    /// Encrypt a session export at rest with AES-256-GCM, keyed by SHA-256(export_key).
+    /// Produces a self-describing `{version, nonce, ciphertext}` envelope as JSON.
+    pub fn export_encrypted(&self, session: &ChatSession, key: &[u8]) -> Result<String, String> {
+        use aes_gcm::{
+            aead::{Aead, KeyInit, OsRng},
+            Aes256Gcm, Nonce,
+        };
+        use base64::{engine::general_purpose::STANDARD, Engine};
+        use sha2::{Digest, Sha256};
+
+        let plaintext = self.export_json(session);
+
+        let derived_key = Sha256::digest(key);
+        let cipher = Aes256Gcm::new_from_slice(&derived_key)
+            .map_err(|e| format!("Failed to initialize cipher: {}", e))?;
+
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|e| format!("Encryption failed: {}", e))?;
+
+        let envelope = EncryptedExport {
+            version: 1,
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        };
+
+        serde_json::to_string(&envelope).map_err(|e| format!("Failed to serialize envelope: {}", e))
+    }
+
+    /// Decrypt and verify an `export_encrypted` envelope, rejecting tampered or
+    /// mis-keyed blobs via the GCM authentication tag.
+    pub fn import_encrypted(&self, envelope_json: &str, key: &[u8]) -> Result<ChatSession, String> {
+        use aes_gcm::{
+            aead::{Aead, KeyInit},
+            Aes256Gcm, Nonce,
+        };
+        use base64::{engine::general_purpose::STANDARD, Engine};
+        use sha2::{Digest, Sha256};
+
+        let envelope: EncryptedExport = serde_json::from_str(envelope_json)
+            .map_err(|e| format!("Invalid export envelope: {}", e))?;
+        if envelope.version != 1 {
+            return Err(format!("Unsupported export version: {}", envelope.version));
+        }
+
+        let nonce_bytes = STANDARD
+            .decode(&envelope.nonce)
+            .map_err(|e| format!("Invalid nonce encoding: {}", e))?;
+        let ciphertext = STANDARD
+            .decode(&envelope.ciphertext)
+            .map_err(|e| format!("Invalid ciphertext encoding: {}", e))?;
+
+        let derived_key = Sha256::digest(key);
+        let cipher = Aes256Gcm::new_from_slice(&derived_key)
+            .map_err(|e| format!("Failed to initialize cipher: {}", e))?;
+        let nonce = Nonce::from_slice(&nonce_bytes);
+
+        let plaintext = cipher
+            .decrypt(nonce, ciphertext.as_ref())
+            .map_err(|_| "Decryption failed: wrong key or tampered export".to_string())?;
+        let plaintext =
+            String::from_utf8(plaintext).map_err(|e| format!("Decrypted data is not valid UTF-8: {}", e))?;
+
+        serde_json::from_str(&plaintext).map_err(|e| format!("Failed to parse decrypted session: {}", e))
+    }
+
     pub fn export_txt(&self, session: &ChatSession) -> String {
         let mut output = format!("Chat Session: {}\n", session.id);
         output.push_str(&format!("Created: {}\n\n", session.created_at.format("%Y-%m-%d %H:%M:%S")));
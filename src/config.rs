@@ -3,12 +3,56 @@ use std::env;
 /// Application configuration loaded from environment variables
 #[derive(Debug, Clone)]
 pub struct Config {
+    /// HMAC-SHA256 hash (hex) of the bearer secret, produced by `middleware::hash_token`.
+    /// The raw secret is never stored or compared directly.
     pub bearer_token: String,
     pub api_host: String,
     pub api_port: u16,
     pub ollama_url: String,
     pub ollama_model: String,
+    /// Model used for `OllamaClient::embed`, distinct from `ollama_model` (the chat model)
+    pub ollama_embedding_model: String,
     pub ollama_enabled: bool,
+    /// Optional bearer token for Ollama deployments that sit behind their own auth
+    pub ollama_auth_token: Option<String>,
+    /// Context window size (`options.num_ctx`) for Ollama `/api/chat` requests, defaulting to 4096
+    pub ollama_num_ctx: Option<usize>,
+    /// Sampling temperature (`options.temperature`) for Ollama `/api/chat` requests
+    pub ollama_temperature: Option<f32>,
+    /// Seed (`options.seed`) for deterministic Ollama `/api/chat` generation
+    pub ollama_seed: Option<i64>,
+    /// Maximum requests per second sent to the Ollama server; `0.0` disables throttling
+    pub max_requests_per_second: f32,
+    /// Which `LlmProvider` backs the chat endpoints: "ollama", "openai" or "anthropic"
+    pub llm_provider: String,
+    pub openai_api_key: Option<String>,
+    pub openai_base_url: String,
+    pub openai_model: String,
+    pub anthropic_api_key: Option<String>,
+    pub anthropic_base_url: String,
+    pub anthropic_model: String,
+    /// Key used to encrypt chat session exports at rest (AES-256-GCM, derived via SHA-256)
+    pub export_key: Option<String>,
+    /// Which `Storage` backend serves learning records and chat sessions: "disk" or "memory"
+    pub storage_backend: String,
+    /// Which `EmbeddingProvider` backs semantic learning-record search: "ollama" or "http"
+    pub embedding_provider: String,
+    /// API key for the OpenAI/Cohere-compatible `/v1/embeddings` provider
+    pub embedding_api_key: Option<String>,
+    pub embedding_base_url: String,
+    pub embedding_model: String,
+    /// Minimum cosine similarity for a record to appear in semantic search results
+    pub semantic_search_threshold: f32,
+    /// Requests per minute allowed per client for read endpoints (GET)
+    pub rate_limit_read_per_min: f64,
+    /// Requests per minute allowed per client for write endpoints (create/update/delete)
+    pub rate_limit_write_per_min: f64,
+    /// Requests per minute allowed per client for bulk/destructive endpoints (`clear`, `purge`)
+    pub rate_limit_bulk_per_min: f64,
+    /// Paths to `SentimentLexicon` language pack files, comma-separated; merged in order,
+    /// later packs overriding earlier ones on token collision. Empty falls back to the
+    /// built-in default pack.
+    pub sentiment_lexicon_paths: Vec<String>,
 }
 
 impl Config {
@@ -32,18 +76,99 @@ impl Config {
         let ollama_model = env::var("OLLAMA_MODEL")
             .unwrap_or_else(|_| "llama2".to_string());
 
+        let ollama_embedding_model = env::var("OLLAMA_EMBEDDING_MODEL")
+            .unwrap_or_else(|_| "nomic-embed-text".to_string());
+
         let ollama_enabled = env::var("OLLAMA_ENABLED")
             .unwrap_or_else(|_| "false".to_string())
             .parse::<bool>()
             .unwrap_or(false);
 
+        let ollama_auth_token = env::var("OLLAMA_AUTH_TOKEN").ok();
+        let ollama_num_ctx = env::var("OLLAMA_NUM_CTX")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .or(Some(4096));
+        let ollama_temperature = env::var("OLLAMA_TEMPERATURE").ok().and_then(|v| v.parse::<f32>().ok());
+        let ollama_seed = env::var("OLLAMA_SEED").ok().and_then(|v| v.parse::<i64>().ok());
+        let max_requests_per_second = env::var("MAX_REQUESTS_PER_SECOND")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<f32>()
+            .unwrap_or(0.0);
+
+        let llm_provider = env::var("LLM_PROVIDER").unwrap_or_else(|_| "ollama".to_string());
+        let openai_api_key = env::var("OPENAI_API_KEY").ok();
+        let openai_base_url = env::var("OPENAI_BASE_URL")
+            .unwrap_or_else(|_| "https://api.openai.com".to_string());
+        let openai_model = env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
+        let anthropic_api_key = env::var("ANTHROPIC_API_KEY").ok();
+        let anthropic_base_url = env::var("ANTHROPIC_BASE_URL")
+            .unwrap_or_else(|_| "https://api.anthropic.com".to_string());
+        let anthropic_model = env::var("ANTHROPIC_MODEL")
+            .unwrap_or_else(|_| "claude-3-5-sonnet-latest".to_string());
+        let export_key = env::var("EXPORT_KEY").ok();
+        let storage_backend = env::var("STORAGE_BACKEND").unwrap_or_else(|_| "disk".to_string());
+
+        let embedding_provider = env::var("EMBEDDING_PROVIDER").unwrap_or_else(|_| "ollama".to_string());
+        let embedding_api_key = env::var("EMBEDDING_API_KEY").ok();
+        let embedding_base_url = env::var("EMBEDDING_BASE_URL")
+            .unwrap_or_else(|_| "https://api.openai.com".to_string());
+        let embedding_model = env::var("EMBEDDING_MODEL")
+            .unwrap_or_else(|_| "text-embedding-3-small".to_string());
+        let semantic_search_threshold = env::var("SEMANTIC_SEARCH_THRESHOLD")
+            .unwrap_or_else(|_| "0.5".to_string())
+            .parse::<f32>()
+            .unwrap_or(0.5);
+
+        let rate_limit_read_per_min = env::var("RATE_LIMIT_READ_PER_MIN")
+            .unwrap_or_else(|_| "120".to_string())
+            .parse::<f64>()
+            .unwrap_or(120.0);
+        let rate_limit_write_per_min = env::var("RATE_LIMIT_WRITE_PER_MIN")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<f64>()
+            .unwrap_or(30.0);
+        let rate_limit_bulk_per_min = env::var("RATE_LIMIT_BULK_PER_MIN")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse::<f64>()
+            .unwrap_or(5.0);
+
+        let sentiment_lexicon_paths = env::var("SENTIMENT_LEXICON_PATHS")
+            .ok()
+            .map(|v| v.split(',').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect())
+            .unwrap_or_default();
+
         Ok(Self {
             bearer_token,
             api_host,
             api_port,
             ollama_url,
             ollama_model,
+            ollama_embedding_model,
             ollama_enabled,
+            ollama_auth_token,
+            ollama_num_ctx,
+            ollama_temperature,
+            ollama_seed,
+            max_requests_per_second,
+            llm_provider,
+            openai_api_key,
+            openai_base_url,
+            openai_model,
+            anthropic_api_key,
+            anthropic_base_url,
+            anthropic_model,
+            export_key,
+            storage_backend,
+            embedding_provider,
+            embedding_api_key,
+            embedding_base_url,
+            embedding_model,
+            semantic_search_threshold,
+            rate_limit_read_per_min,
+            rate_limit_write_per_min,
+            rate_limit_bulk_per_min,
+            sentiment_lexicon_paths,
         })
     }
 
@@ -54,6 +179,6 @@ impl Config {
 
     /// Get Ollama API endpoint
     pub fn ollama_api_endpoint(&self) -> String {
-        format!("{}/api/generate", self.ollama_url)
+        format!("{}/api/chat", self.ollama_url)
     }
 }
@@ -60,9 +60,9 @@ impl DecisionMaker {
         }
     }
 
-    /// Make decision with custom query
+    /// Make decision with custom query, ranked by BM25 relevance rather than substring matching
     pub fn make_decision_for_query(mem: &Memory, query: &str) -> Decision {
-        let relevant = mem.search(query);
+        let relevant = mem.search_bm25(query, 20);
         let count = relevant.len();
 
         if count == 0 {
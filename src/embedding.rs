@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+/// Common interface for embedding backends so semantic search can target
+/// local Ollama, OpenAI, Cohere, or any OpenAI-compatible `/embeddings`
+/// endpoint without code changes.
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Compute an embedding vector for `text`
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, String>;
+
+    /// Whether this provider is currently configured and usable
+    fn is_enabled(&self) -> bool;
+}
+
+#[async_trait]
+impl EmbeddingProvider for crate::ollama::OllamaClient {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, String> {
+        crate::ollama::OllamaClient::embed(self, text).await
+    }
+
+    fn is_enabled(&self) -> bool {
+        crate::ollama::OllamaClient::is_enabled(self)
+    }
+}
+
+/// OpenAI-compatible provider, targeting `/v1/embeddings` with a Bearer API
+/// key (Cohere and most other hosted embedding APIs speak this same shape)
+pub struct HttpEmbeddingProvider {
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingDatum {
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingDatum>,
+}
+
+impl HttpEmbeddingProvider {
+    pub fn new(api_key: String, base_url: String, model: String) -> Self {
+        Self {
+            api_key,
+            base_url,
+            model,
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for HttpEmbeddingProvider {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, String> {
+        if !self.is_enabled() {
+            return Err("Embedding provider is not configured (missing EMBEDDING_API_KEY)".to_string());
+        }
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/v1/embeddings", self.base_url);
+
+        let response = client
+            .post(&endpoint)
+            .bearer_auth(&self.api_key)
+            .json(&serde_json::json!({
+                "model": self.model,
+                "input": text,
+            }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to embedding endpoint: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Embedding API error ({}): {}", status, body));
+        }
+
+        let parsed: EmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse embedding response: {}", e))?;
+
+        parsed
+            .data
+            .into_iter()
+            .next()
+            .map(|d| d.embedding)
+            .ok_or_else(|| "Embedding response contained no data".to_string())
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+}
+
+/// Scale `vector` to unit length in place, so ranking by dot product alone
+/// is equivalent to ranking by cosine similarity. A zero vector is left
+/// unchanged rather than dividing by zero.
+pub fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for v in vector.iter_mut() {
+            *v /= norm;
+        }
+    }
+}
+
+/// Dot product of two equal-length vectors, which is cosine similarity when
+/// both are already unit-normalized
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
@@ -10,6 +10,12 @@ pub struct Experience {
     pub content: String,
     #[serde(default)]
     pub metadata: Option<String>,
+    /// Embedding vector computed for `content` at `remember` time, so
+    /// semantic search can rank stored vectors instead of re-embedding
+    /// every experience on every query. `None` if embedding failed (e.g.
+    /// Ollama unreachable) — `Memory::search_semantic` skips those.
+    #[serde(default)]
+    pub embedding: Option<Vec<f32>>,
 }
 
 impl Experience {
@@ -22,6 +28,7 @@ impl Experience {
             source: source.to_string(),
             content: content.to_string(),
             metadata: None,
+            embedding: None,
         }
     }
 
@@ -31,4 +38,10 @@ impl Experience {
         exp.metadata = Some(metadata);
         exp
     }
+
+    /// Attach a precomputed embedding vector, so semantic search can skip re-embedding at query time
+    pub fn with_embedding(mut self, embedding: Option<Vec<f32>>) -> Self {
+        self.embedding = embedding;
+        self
+    }
 }
@@ -0,0 +1,69 @@
+use crate::experience::Experience;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Persists experiences to disk, mirroring `SessionStore`'s load/persist
+/// pattern so experiences survive restarts independent of `Memory`'s
+/// own periodic background save.
+pub struct ExperienceStore {
+    path: PathBuf,
+    experiences: RwLock<Vec<Experience>>,
+}
+
+impl ExperienceStore {
+    /// Load experiences from `path` if it exists, otherwise start empty
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let experiences = Self::load(&path).unwrap_or_default();
+        Self {
+            path,
+            experiences: RwLock::new(experiences),
+        }
+    }
+
+    fn load(path: &PathBuf) -> Option<Vec<Experience>> {
+        let contents = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    fn persist(&self) {
+        let experiences = match self.experiences.read() {
+            Ok(e) => e,
+            Err(_) => return,
+        };
+        let json = match serde_json::to_string_pretty(&*experiences) {
+            Ok(j) => j,
+            Err(e) => {
+                tracing::error!("Failed to serialize experiences: {}", e);
+                return;
+            }
+        };
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Err(e) = fs::write(&self.path, json) {
+            tracing::error!("Failed to persist experiences to {:?}: {}", self.path, e);
+        }
+    }
+
+    /// Append an experience and persist
+    pub fn add(&self, experience: Experience) {
+        self.experiences.write().unwrap().push(experience);
+        self.persist();
+    }
+
+    pub fn all(&self) -> Vec<Experience> {
+        self.experiences.read().unwrap().clone()
+    }
+
+    /// Remove every stored experience and persist, returning how many were removed
+    pub fn clear(&self) -> usize {
+        let mut experiences = self.experiences.write().unwrap();
+        let count = experiences.len();
+        experiences.clear();
+        drop(experiences);
+        self.persist();
+        count
+    }
+}
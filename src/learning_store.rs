@@ -0,0 +1,388 @@
+use crate::chat::ApiLearningRecord;
+use crate::search::InvertedIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// BM25 parameters for learning-record search, matching the spec default
+/// (also used for experience search in `memory.rs`)
+const SEARCH_K1: f32 = 1.2;
+const SEARCH_B: f32 = 0.75;
+
+/// Field weights so a match in `tags` ranks above `summary`, which in turn
+/// ranks above `url`
+const TAGS_WEIGHT: f32 = 3.0;
+const SUMMARY_WEIGHT: f32 = 1.5;
+const URL_WEIGHT: f32 = 1.0;
+
+/// One entry in a record's version history. `record: None` is a tombstone —
+/// the state left behind by a soft delete.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordVersion {
+    /// Monotonically increasing, derived from `max(now, latest + 1)` so
+    /// clock skew or two versions in the same millisecond can't collide
+    pub version: i64,
+    pub record: Option<ApiLearningRecord>,
+}
+
+/// What's written to disk: the live snapshot (for fast reads) plus the full
+/// append-only version history (for `/versions` and `/restore`)
+#[derive(Default, Serialize, Deserialize)]
+struct PersistedState {
+    records: HashMap<String, ApiLearningRecord>,
+    history: HashMap<String, Vec<RecordVersion>>,
+}
+
+/// Persists API learning records to disk, mirroring `SessionStore`'s pattern
+/// so experiences, chat sessions, and learning records all survive restarts.
+/// Keeps a BM25 inverted index over `url`/`tags`/`summary` in sync with every
+/// insert/remove so search results are ranked rather than filtered. Also
+/// caches embedding vectors per record id for semantic search, keyed
+/// separately since computing them requires an external provider call.
+///
+/// Deletes are soft: `remove` appends a tombstone version rather than
+/// erasing anything, so `restore` can reinstate the last live version and
+/// `versions` can list the full history. `purge` is the only hard delete.
+pub struct LearningRecordStore {
+    path: PathBuf,
+    records: RwLock<HashMap<String, ApiLearningRecord>>,
+    history: RwLock<HashMap<String, Vec<RecordVersion>>>,
+    index: RwLock<InvertedIndex>,
+    /// Cached, unit-normalized embedding vectors by record id, so semantic
+    /// search doesn't re-call the embedding provider on every query
+    embeddings: RwLock<HashMap<String, Vec<f32>>>,
+}
+
+/// Index `record`'s searchable fields under its id, replacing any existing entry
+fn index_record(index: &mut InvertedIndex, record: &ApiLearningRecord) {
+    let tags_text = record.tags.join(" ");
+    index.index_weighted(
+        &record.id,
+        &[
+            (record.url.as_str(), URL_WEIGHT),
+            (tags_text.as_str(), TAGS_WEIGHT),
+            (record.summary.as_str(), SUMMARY_WEIGHT),
+        ],
+    );
+}
+
+/// Next version number for a history, strictly greater than the last entry
+/// even if the wall clock hasn't advanced since
+pub(crate) fn next_version(history: &[RecordVersion]) -> i64 {
+    let now = chrono::Utc::now().timestamp_millis();
+    let latest = history.last().map(|v| v.version).unwrap_or(0);
+    now.max(latest + 1)
+}
+
+impl LearningRecordStore {
+    /// Load records from `path` if it exists, otherwise start empty
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let state = Self::load(&path).unwrap_or_default();
+
+        let mut index = InvertedIndex::with_params(SEARCH_K1, SEARCH_B);
+        for record in state.records.values() {
+            index_record(&mut index, record);
+        }
+
+        Self {
+            path,
+            records: RwLock::new(state.records),
+            history: RwLock::new(state.history),
+            index: RwLock::new(index),
+            embeddings: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn load(path: &PathBuf) -> Option<PersistedState> {
+        let contents = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    fn persist(&self) {
+        let records = match self.records.read() {
+            Ok(r) => r,
+            Err(_) => return,
+        };
+        let history = match self.history.read() {
+            Ok(h) => h,
+            Err(_) => return,
+        };
+        let state = PersistedState {
+            records: records.clone(),
+            history: history.clone(),
+        };
+        let json = match serde_json::to_string_pretty(&state) {
+            Ok(j) => j,
+            Err(e) => {
+                tracing::error!("Failed to serialize learning records: {}", e);
+                return;
+            }
+        };
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Err(e) = fs::write(&self.path, json) {
+            tracing::error!("Failed to persist learning records to {:?}: {}", self.path, e);
+        }
+    }
+
+    /// Create or overwrite a record, appending a new live version to its history
+    pub fn insert(&self, record: ApiLearningRecord) {
+        index_record(&mut self.index.write().unwrap(), &record);
+        {
+            let mut history = self.history.write().unwrap();
+            let entry = history.entry(record.id.clone()).or_default();
+            let version = next_version(entry);
+            entry.push(RecordVersion {
+                version,
+                record: Some(record.clone()),
+            });
+        }
+        self.records.write().unwrap().insert(record.id.clone(), record);
+        self.persist();
+    }
+
+    pub fn get(&self, id: &str) -> Option<ApiLearningRecord> {
+        self.records.read().unwrap().get(id).cloned()
+    }
+
+    pub fn all(&self) -> Vec<ApiLearningRecord> {
+        self.records.read().unwrap().values().cloned().collect()
+    }
+
+    /// Soft-delete a record: append a tombstone version and drop it from the
+    /// live snapshot and search index. Returns whether a live record existed.
+    pub fn remove(&self, id: &str) -> bool {
+        let removed = self.records.write().unwrap().remove(id).is_some();
+        if removed {
+            let mut history = self.history.write().unwrap();
+            if let Some(entry) = history.get_mut(id) {
+                let version = next_version(entry);
+                entry.push(RecordVersion {
+                    version,
+                    record: None,
+                });
+            }
+            drop(history);
+            self.index.write().unwrap().remove(id);
+            self.embeddings.write().unwrap().remove(id);
+            self.persist();
+        }
+        removed
+    }
+
+    /// Full version history for `id`, oldest first, or `None` if the id has never existed
+    pub fn versions(&self, id: &str) -> Option<Vec<RecordVersion>> {
+        self.history.read().unwrap().get(id).cloned()
+    }
+
+    /// Reinstate the last non-deleted version of `id` as a new current
+    /// version. Returns `None` if `id` is already live or has no prior
+    /// non-deleted version to restore.
+    pub fn restore(&self, id: &str) -> Option<ApiLearningRecord> {
+        if self.records.read().unwrap().contains_key(id) {
+            return None;
+        }
+        let restored = {
+            let mut history = self.history.write().unwrap();
+            let entry = history.get_mut(id)?;
+            let last_live = entry.iter().rev().find_map(|v| v.record.clone())?;
+            let version = next_version(entry);
+            entry.push(RecordVersion {
+                version,
+                record: Some(last_live.clone()),
+            });
+            last_live
+        };
+        index_record(&mut self.index.write().unwrap(), &restored);
+        self.records.write().unwrap().insert(id.to_string(), restored.clone());
+        self.persist();
+        Some(restored)
+    }
+
+    /// Cached embedding for `id`, if one has been computed
+    pub fn embedding(&self, id: &str) -> Option<Vec<f32>> {
+        self.embeddings.read().unwrap().get(id).cloned()
+    }
+
+    /// Cache `vector` as `id`'s embedding, replacing any previous one
+    pub fn cache_embedding(&self, id: &str, vector: Vec<f32>) {
+        self.embeddings.write().unwrap().insert(id.to_string(), vector);
+    }
+
+    /// All cached (record, embedding) pairs whose record still exists
+    pub fn embeddings(&self) -> Vec<(ApiLearningRecord, Vec<f32>)> {
+        let records = self.records.read().unwrap();
+        self.embeddings
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|(id, vector)| records.get(id).map(|r| (r.clone(), vector.clone())))
+            .collect()
+    }
+
+    /// Search records by BM25-ranked relevance over `url`/`tags`/`summary`
+    /// (with typo tolerance), instead of a linear substring scan. Returns
+    /// matches with their score, sorted by descending score.
+    pub fn search(&self, query: &str, top_n: usize) -> Vec<(ApiLearningRecord, f32)> {
+        let records = self.records.read().unwrap();
+        self.index
+            .read()
+            .unwrap()
+            .search(query, top_n)
+            .into_iter()
+            .filter_map(|(id, score)| records.get(&id).map(|r| (r.clone(), score)))
+            .collect()
+    }
+
+    /// Cursor-paginated, optionally filtered listing of records,
+    /// CHATHISTORY-style. `before`/`after` are record ids forming an
+    /// exclusive bound over the sort order; `tag` and `created_after` narrow
+    /// the set before windowing. The sort key (`learned_at`, tie-broken by
+    /// `id`) stays fixed regardless of `descending`, so cursors stay valid
+    /// across requests even as records are inserted or deleted elsewhere.
+    pub fn paginate(
+        &self,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+        tag: Option<&str>,
+        created_after: Option<chrono::DateTime<chrono::Utc>>,
+        descending: bool,
+    ) -> Result<(Vec<ApiLearningRecord>, Option<String>, Option<String>), String> {
+        let records = self.records.read().unwrap();
+        let mut items: Vec<&ApiLearningRecord> = records
+            .values()
+            .filter(|r| tag.map_or(true, |t| r.tags.iter().any(|rt| rt == t)))
+            .filter(|r| created_after.map_or(true, |ts| r.learned_at > ts))
+            .collect();
+
+        if descending {
+            items.sort_by(|a, b| b.learned_at.cmp(&a.learned_at).then_with(|| b.id.cmp(&a.id)));
+        } else {
+            items.sort_by(|a, b| a.learned_at.cmp(&b.learned_at).then_with(|| a.id.cmp(&b.id)));
+        }
+
+        if let Some(cursor) = before {
+            let idx = items
+                .iter()
+                .position(|r| r.id == cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            items.truncate(idx);
+        }
+        if let Some(cursor) = after {
+            let idx = items
+                .iter()
+                .position(|r| r.id == cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            items = items.split_off(idx + 1);
+        }
+
+        let page: Vec<ApiLearningRecord> = if after.is_none() {
+            let skip = items.len().saturating_sub(limit);
+            items.split_off(skip).into_iter().cloned().collect()
+        } else {
+            items.truncate(limit);
+            items.into_iter().cloned().collect()
+        };
+
+        let next_cursor = page.last().map(|r| r.id.clone());
+        let prev_cursor = page.first().map(|r| r.id.clone());
+        Ok((page, next_cursor, prev_cursor))
+    }
+
+    /// Cursor-paginated BM25 search: results are fully ranked first (score
+    /// descending, id ascending as a tiebreaker for stability), then
+    /// windowed the same way as `paginate`, so cursors stay valid as the
+    /// corpus is edited elsewhere between requests.
+    pub fn search_page(
+        &self,
+        query: &str,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<(ApiLearningRecord, f32)>, Option<String>, Option<String>), String> {
+        let records = self.records.read().unwrap();
+        let mut ranked: Vec<(ApiLearningRecord, f32)> = self
+            .index
+            .read()
+            .unwrap()
+            .search(query, usize::MAX)
+            .into_iter()
+            .filter_map(|(id, score)| records.get(&id).map(|r| (r.clone(), score)))
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.id.cmp(&b.0.id))
+        });
+
+        if let Some(cursor) = before {
+            let idx = ranked
+                .iter()
+                .position(|(r, _)| r.id == cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            ranked.truncate(idx);
+        }
+        if let Some(cursor) = after {
+            let idx = ranked
+                .iter()
+                .position(|(r, _)| r.id == cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            ranked = ranked.split_off(idx + 1);
+        }
+
+        let page = if after.is_none() {
+            let skip = ranked.len().saturating_sub(limit);
+            ranked.split_off(skip)
+        } else {
+            ranked.truncate(limit);
+            ranked
+        };
+
+        let next_cursor = page.last().map(|(r, _)| r.id.clone());
+        let prev_cursor = page.first().map(|(r, _)| r.id.clone());
+        Ok((page, next_cursor, prev_cursor))
+    }
+
+    /// Soft-delete every live record, returning how many were tombstoned
+    pub fn clear(&self) -> usize {
+        let mut records = self.records.write().unwrap();
+        let mut history = self.history.write().unwrap();
+        let mut count = 0;
+        for id in records.keys() {
+            if let Some(entry) = history.get_mut(id) {
+                let version = next_version(entry);
+                entry.push(RecordVersion {
+                    version,
+                    record: None,
+                });
+                count += 1;
+            }
+        }
+        records.clear();
+        drop(records);
+        drop(history);
+        *self.index.write().unwrap() = InvertedIndex::with_params(SEARCH_K1, SEARCH_B);
+        self.embeddings.write().unwrap().clear();
+        self.persist();
+        count
+    }
+
+    /// Permanently erase every version of every record, live or tombstoned.
+    /// Unlike `clear`, this cannot be undone with `restore`.
+    pub fn purge(&self) -> usize {
+        let mut history = self.history.write().unwrap();
+        let count = history.len();
+        history.clear();
+        drop(history);
+        self.records.write().unwrap().clear();
+        *self.index.write().unwrap() = InvertedIndex::with_params(SEARCH_K1, SEARCH_B);
+        self.embeddings.write().unwrap().clear();
+        self.persist();
+        count
+    }
+}
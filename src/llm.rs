@@ -0,0 +1,522 @@
+use futures_util::Stream;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// One prior turn of a conversation (role + content), threaded into the
+/// message list sent to a provider so it sees more than just the latest
+/// user message
+#[derive(Debug, Clone)]
+pub struct ConversationTurn {
+    pub role: String,
+    pub content: String,
+}
+
+/// Common interface for language-model backends so `ChatProcessor` can target
+/// local Ollama, OpenAI, or any OpenAI-compatible gateway without code changes.
+#[async_trait::async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Generate a full completion given the user input, memory context
+    /// snippets, and prior conversation turns (oldest first)
+    async fn generate_with_context(
+        &self,
+        user_input: &str,
+        context: &[String],
+        history: &[ConversationTurn],
+    ) -> Result<String, String>;
+
+    /// Whether this provider is currently configured and usable
+    fn is_enabled(&self) -> bool;
+
+    /// Generate a completion as a stream of incremental text chunks
+    async fn generate_with_context_stream(
+        &self,
+        user_input: &str,
+        context: &[String],
+        history: &[ConversationTurn],
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<String, String>> + Send>>, String>;
+}
+
+/// Registry of named `LlmProvider` backends. New backends register themselves
+/// by name at startup; callers that just want "the configured provider" look
+/// it up by name instead of matching on a hardcoded set of variants.
+#[derive(Default, Clone)]
+pub struct LlmRegistry {
+    clients: HashMap<String, Arc<dyn LlmProvider>>,
+}
+
+impl LlmRegistry {
+    pub fn new() -> Self {
+        Self {
+            clients: HashMap::new(),
+        }
+    }
+
+    /// Register a backend under `name`, overwriting any previous registration
+    pub fn register_client(&mut self, name: impl Into<String>, client: Arc<dyn LlmProvider>) {
+        self.clients.insert(name.into(), client);
+    }
+
+    /// Build and register every backend described by `configs`, each keyed by its `name()`
+    pub fn register_from_configs(&mut self, configs: &[ClientConfig]) {
+        for config in configs {
+            self.register_client(config.name(), config.init());
+        }
+    }
+
+    /// Look up a previously registered backend by name
+    pub fn get(&self, name: &str) -> Option<Arc<dyn LlmProvider>> {
+        self.clients.get(name).cloned()
+    }
+
+    /// Names of all registered backends
+    pub fn client_names(&self) -> Vec<&str> {
+        self.clients.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmProvider for crate::ollama::OllamaClient {
+    async fn generate_with_context(
+        &self,
+        user_input: &str,
+        context: &[String],
+        history: &[ConversationTurn],
+    ) -> Result<String, String> {
+        crate::ollama::OllamaClient::generate_with_context(self, user_input, context, history).await
+    }
+
+    fn is_enabled(&self) -> bool {
+        crate::ollama::OllamaClient::is_enabled(self)
+    }
+
+    async fn generate_with_context_stream(
+        &self,
+        user_input: &str,
+        context: &[String],
+        history: &[ConversationTurn],
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<String, String>> + Send>>, String> {
+        let stream =
+            crate::ollama::OllamaClient::generate_stream_with_context(self, user_input, context, history).await?;
+        Ok(Box::pin(stream))
+    }
+}
+
+/// OpenAI-compatible provider, targeting `/v1/chat/completions` with a Bearer API key
+pub struct OpenAiProvider {
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+#[derive(Deserialize)]
+struct OpenAiChoice {
+    message: OpenAiMessage,
+}
+
+#[derive(Deserialize)]
+struct OpenAiMessage {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct OpenAiCompletion {
+    choices: Vec<OpenAiChoice>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiStreamDelta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiStreamChoice {
+    delta: OpenAiStreamDelta,
+}
+
+#[derive(Deserialize)]
+struct OpenAiStreamChunk {
+    choices: Vec<OpenAiStreamChoice>,
+}
+
+impl OpenAiProvider {
+    pub fn new(api_key: String, base_url: String, model: String) -> Self {
+        Self {
+            api_key,
+            base_url,
+            model,
+        }
+    }
+
+    fn build_messages(
+        &self,
+        user_input: &str,
+        context: &[String],
+        history: &[ConversationTurn],
+    ) -> serde_json::Value {
+        let mut messages = Vec::new();
+        if !context.is_empty() {
+            messages.push(serde_json::json!({
+                "role": "system",
+                "content": format!("Context from memory:\n{}", context.join("\n")),
+            }));
+        }
+        for turn in history {
+            messages.push(serde_json::json!({
+                "role": turn.role,
+                "content": turn.content,
+            }));
+        }
+        messages.push(serde_json::json!({
+            "role": "user",
+            "content": user_input,
+        }));
+        serde_json::Value::Array(messages)
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmProvider for OpenAiProvider {
+    async fn generate_with_context(
+        &self,
+        user_input: &str,
+        context: &[String],
+        history: &[ConversationTurn],
+    ) -> Result<String, String> {
+        if !self.is_enabled() {
+            return Err("OpenAI provider is not configured (missing OPENAI_API_KEY)".to_string());
+        }
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/v1/chat/completions", self.base_url);
+
+        let response = client
+            .post(&endpoint)
+            .bearer_auth(&self.api_key)
+            .json(&serde_json::json!({
+                "model": self.model,
+                "messages": self.build_messages(user_input, context, history),
+            }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to OpenAI-compatible endpoint: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("OpenAI API error ({}): {}", status, body));
+        }
+
+        let completion: OpenAiCompletion = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse OpenAI response: {}", e))?;
+
+        completion
+            .choices
+            .into_iter()
+            .next()
+            .map(|c| c.message.content)
+            .ok_or_else(|| "OpenAI response contained no choices".to_string())
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
+    async fn generate_with_context_stream(
+        &self,
+        user_input: &str,
+        context: &[String],
+        history: &[ConversationTurn],
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<String, String>> + Send>>, String> {
+        use futures_util::StreamExt;
+
+        if !self.is_enabled() {
+            return Err("OpenAI provider is not configured (missing OPENAI_API_KEY)".to_string());
+        }
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/v1/chat/completions", self.base_url);
+
+        let response = client
+            .post(&endpoint)
+            .bearer_auth(&self.api_key)
+            .json(&serde_json::json!({
+                "model": self.model,
+                "messages": self.build_messages(user_input, context, history),
+                "stream": true,
+            }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to OpenAI-compatible endpoint: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("OpenAI API error: {}", response.status()));
+        }
+
+        use tokio::io::AsyncBufReadExt;
+        use tokio_stream::wrappers::LinesStream;
+        use tokio_util::io::StreamReader;
+
+        let byte_stream = response
+            .bytes_stream()
+            .map(|r| r.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)));
+        let reader = StreamReader::new(byte_stream);
+        let lines = LinesStream::new(reader.lines());
+
+        let token_stream = lines.map(|line| {
+            let line = line.map_err(|e| format!("Stream read error: {}", e))?;
+            let data = match line.strip_prefix("data: ") {
+                Some(d) => d.trim(),
+                None => return Ok(String::new()),
+            };
+            if data == "[DONE]" || data.is_empty() {
+                return Ok(String::new());
+            }
+            let chunk: OpenAiStreamChunk = serde_json::from_str(data)
+                .map_err(|e| format!("Failed to parse OpenAI stream chunk: {}", e))?;
+            Ok(chunk
+                .choices
+                .into_iter()
+                .next()
+                .and_then(|c| c.delta.content)
+                .unwrap_or_default())
+        });
+
+        Ok(Box::pin(token_stream))
+    }
+}
+
+/// Anthropic Messages API version this client speaks
+const ANTHROPIC_API_VERSION: &str = "2023-06-01";
+
+/// Anthropic provider, targeting `/v1/messages` with an `x-api-key` header.
+/// Unlike OpenAI, Anthropic takes the system prompt as a top-level field
+/// rather than a `"system"`-role message.
+pub struct AnthropicProvider {
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+#[derive(Deserialize)]
+struct AnthropicContentBlock {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct AnthropicMessageResponse {
+    #[serde(default)]
+    content: Vec<AnthropicContentBlock>,
+}
+
+#[derive(Deserialize)]
+struct AnthropicStreamDelta {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AnthropicStreamEvent {
+    #[serde(default)]
+    delta: Option<AnthropicStreamDelta>,
+}
+
+impl AnthropicProvider {
+    pub fn new(api_key: String, base_url: String, model: String) -> Self {
+        Self {
+            api_key,
+            base_url,
+            model,
+        }
+    }
+
+    /// Build the `system` field and role-tagged `messages` array. Memory context
+    /// is folded into the system prompt since Anthropic has no system-role message.
+    fn build_request_body(
+        &self,
+        user_input: &str,
+        context: &[String],
+        history: &[ConversationTurn],
+        stream: bool,
+    ) -> serde_json::Value {
+        let mut messages: Vec<serde_json::Value> = history
+            .iter()
+            .map(|turn| serde_json::json!({ "role": turn.role, "content": turn.content }))
+            .collect();
+        messages.push(serde_json::json!({ "role": "user", "content": user_input }));
+
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "max_tokens": 4096,
+            "messages": messages,
+            "stream": stream,
+        });
+        if !context.is_empty() {
+            body["system"] = serde_json::Value::String(format!("Context from memory:\n{}", context.join("\n")));
+        }
+        body
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmProvider for AnthropicProvider {
+    async fn generate_with_context(
+        &self,
+        user_input: &str,
+        context: &[String],
+        history: &[ConversationTurn],
+    ) -> Result<String, String> {
+        if !self.is_enabled() {
+            return Err("Anthropic provider is not configured (missing ANTHROPIC_API_KEY)".to_string());
+        }
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/v1/messages", self.base_url);
+
+        let response = client
+            .post(&endpoint)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_API_VERSION)
+            .json(&self.build_request_body(user_input, context, history, false))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to Anthropic endpoint: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Anthropic API error ({}): {}", status, body));
+        }
+
+        let completion: AnthropicMessageResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse Anthropic response: {}", e))?;
+
+        completion
+            .content
+            .into_iter()
+            .next()
+            .map(|block| block.text)
+            .ok_or_else(|| "Anthropic response contained no content blocks".to_string())
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
+    async fn generate_with_context_stream(
+        &self,
+        user_input: &str,
+        context: &[String],
+        history: &[ConversationTurn],
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<String, String>> + Send>>, String> {
+        use futures_util::StreamExt;
+
+        if !self.is_enabled() {
+            return Err("Anthropic provider is not configured (missing ANTHROPIC_API_KEY)".to_string());
+        }
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/v1/messages", self.base_url);
+
+        let response = client
+            .post(&endpoint)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_API_VERSION)
+            .json(&self.build_request_body(user_input, context, history, true))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to Anthropic endpoint: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Anthropic API error: {}", response.status()));
+        }
+
+        use tokio::io::AsyncBufReadExt;
+        use tokio_stream::wrappers::LinesStream;
+        use tokio_util::io::StreamReader;
+
+        let byte_stream = response
+            .bytes_stream()
+            .map(|r| r.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)));
+        let reader = StreamReader::new(byte_stream);
+        let lines = LinesStream::new(reader.lines());
+
+        // Anthropic streams Server-Sent Events; only `data:` lines carry payloads,
+        // and only `content_block_delta` events carry text fragments.
+        let token_stream = lines.map(|line| {
+            let line = line.map_err(|e| format!("Stream read error: {}", e))?;
+            let data = match line.strip_prefix("data: ") {
+                Some(d) => d.trim(),
+                None => return Ok(String::new()),
+            };
+            if data.is_empty() {
+                return Ok(String::new());
+            }
+            let event: AnthropicStreamEvent = match serde_json::from_str(data) {
+                Ok(event) => event,
+                Err(_) => return Ok(String::new()), // non-delta events (message_start, message_stop, ...)
+            };
+            Ok(event.delta.and_then(|d| d.text).unwrap_or_default())
+        });
+
+        Ok(Box::pin(token_stream))
+    }
+}
+
+/// Per-provider client configuration tagged by `"type"`, so a single list can
+/// describe heterogeneous hosted backends (mirrors aichat's client registry).
+/// Ollama is configured separately since it also needs `health_check`/
+/// `preload_model`/`embed`, which need the concrete `OllamaClient` type.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientConfig {
+    Openai {
+        api_key: String,
+        #[serde(default = "default_openai_base_url")]
+        base_url: String,
+        model: String,
+    },
+    Anthropic {
+        api_key: String,
+        #[serde(default = "default_anthropic_base_url")]
+        base_url: String,
+        model: String,
+    },
+}
+
+fn default_openai_base_url() -> String {
+    "https://api.openai.com".to_string()
+}
+
+fn default_anthropic_base_url() -> String {
+    "https://api.anthropic.com".to_string()
+}
+
+impl ClientConfig {
+    /// Name this config registers under in an `LlmRegistry`, matching its `type` tag
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClientConfig::Openai { .. } => "openai",
+            ClientConfig::Anthropic { .. } => "anthropic",
+        }
+    }
+
+    /// Build the concrete provider described by this config
+    pub fn init(&self) -> Arc<dyn LlmProvider> {
+        match self {
+            ClientConfig::Openai { api_key, base_url, model } => {
+                Arc::new(OpenAiProvider::new(api_key.clone(), base_url.clone(), model.clone()))
+            }
+            ClientConfig::Anthropic { api_key, base_url, model } => {
+                Arc::new(AnthropicProvider::new(api_key.clone(), base_url.clone(), model.clone()))
+            }
+        }
+    }
+}
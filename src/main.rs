@@ -3,12 +3,23 @@ mod chat;
 mod config;
 mod decision;
 mod dialog;
+mod embedding;
 mod experience;
+mod experience_store;
+mod learning_store;
+mod llm;
 mod memory;
 mod middleware;
 mod ollama;
 mod pattern;
 mod personality;
+mod qlearning;
+mod rate_limit;
+mod realtime;
+mod search;
+mod sentiment;
+mod session_store;
+mod storage;
 
 use axum::{
     middleware as axum_middleware,
@@ -43,11 +54,19 @@ async fn main() {
     }
 
     // Initialize Ollama client
-    let ollama_client = Arc::new(ollama::OllamaClient::new(
-        config.ollama_url.clone(),
-        config.ollama_model.clone(),
-        config.ollama_enabled,
-    ));
+    let ollama_client = Arc::new(
+        ollama::OllamaClient::new(
+            config.ollama_url.clone(),
+            config.ollama_model.clone(),
+            config.ollama_enabled,
+        )
+        .with_embedding_model(config.ollama_embedding_model.clone())
+        .with_auth_token(config.ollama_auth_token.clone())
+        .with_num_ctx(config.ollama_num_ctx)
+        .with_temperature(config.ollama_temperature)
+        .with_seed(config.ollama_seed)
+        .with_max_requests_per_second(config.max_requests_per_second),
+    );
 
     // Health check Ollama if enabled
     if config.ollama_enabled {
@@ -64,6 +83,13 @@ async fn main() {
                     tracing::warn!("   Failed to list models: {}", e);
                 }
             }
+
+            // Preload the configured model so the first chat request doesn't pay cold-start latency
+            tracing::info!("   Preloading model '{}'...", config.ollama_model);
+            match ollama_client.preload_model().await {
+                Ok(()) => tracing::info!("   ✅ Model preloaded"),
+                Err(e) => tracing::warn!("   Failed to preload model: {}", e),
+            }
         } else {
             tracing::warn!("   ⚠️  Ollama is not accessible. Chat will use fallback responses.");
             tracing::warn!("   Make sure Ollama is running: ollama serve");
@@ -81,8 +107,75 @@ async fn main() {
         tracing::info!("   Starting with fresh memory");
     }
 
+    // Register available LLM backends, keyed by the name used in LLM_PROVIDER.
+    // Ollama is wired up directly since it also needs health_check/preload_model/embed,
+    // which require the concrete client rather than the `LlmProvider` trait object.
+    // OpenAI and Anthropic are hosted API clients described by a `ClientConfig`, tagged
+    // by provider type, that `init()`s the matching backend.
+    let mut llm_registry = llm::LlmRegistry::new();
+    llm_registry.register_client("ollama", ollama_client.clone() as Arc<dyn llm::LlmProvider>);
+    let mut hosted_client_configs = Vec::new();
+    if let Some(api_key) = config.openai_api_key.clone() {
+        hosted_client_configs.push(llm::ClientConfig::Openai {
+            api_key,
+            base_url: config.openai_base_url.clone(),
+            model: config.openai_model.clone(),
+        });
+    }
+    if let Some(api_key) = config.anthropic_api_key.clone() {
+        hosted_client_configs.push(llm::ClientConfig::Anthropic {
+            api_key,
+            base_url: config.anthropic_base_url.clone(),
+            model: config.anthropic_model.clone(),
+        });
+    }
+    llm_registry.register_from_configs(&hosted_client_configs);
+    let llm_registry = Arc::new(llm_registry);
+
     // Create application state
-    let app_state = api::AppState::new(memory.clone(), ollama_client.clone());
+    let config = Arc::new(config);
+    let embedding_provider: Arc<dyn embedding::EmbeddingProvider> = match config
+        .embedding_provider
+        .as_str()
+    {
+        "http" => Arc::new(embedding::HttpEmbeddingProvider::new(
+            config.embedding_api_key.clone().unwrap_or_default(),
+            config.embedding_base_url.clone(),
+            config.embedding_model.clone(),
+        )),
+        _ => ollama_client.clone() as Arc<dyn embedding::EmbeddingProvider>,
+    };
+    let storage: Arc<dyn storage::Storage> = match config.storage_backend.as_str() {
+        "memory" => Arc::new(storage::InMemoryStorage::with_embedding_provider(
+            embedding_provider,
+        )),
+        _ => Arc::new(storage::DiskStorage::new(
+            "data/learning_records.json",
+            "data/sessions.json",
+            embedding_provider,
+        )),
+    };
+    let realtime = Arc::new(realtime::SessionRegistry::new());
+    let rate_limiter = Arc::new(rate_limit::RateLimiter::new(rate_limit::RateLimits {
+        read_per_min: config.rate_limit_read_per_min,
+        write_per_min: config.rate_limit_write_per_min,
+        bulk_per_min: config.rate_limit_bulk_per_min,
+    }));
+    let qlearner = Arc::new(qlearning::QLearner::new("data/qtable.json"));
+    let sentiment_lexicon = Arc::new(sentiment::SentimentLexicon::load_packs(
+        &config.sentiment_lexicon_paths,
+    ));
+    let app_state = api::AppState::new(
+        memory.clone(),
+        ollama_client.clone(),
+        config.clone(),
+        storage,
+        llm_registry,
+        realtime.clone(),
+        rate_limiter.clone(),
+        qlearner,
+        sentiment_lexicon,
+    );
 
     // Build protected routes (require authentication)
     let protected_routes = Router::new()
@@ -90,6 +183,8 @@ async fn main() {
         .route("/experiences/:id", get(api::get_experience_by_id))
         .route("/experiences", post(api::create_experience))
         .route("/experiences/search", get(api::search_experiences))
+        .route("/experiences/search/semantic", get(api::search_experiences_semantic))
+        .route("/experiences/search/fulltext", get(api::search_experiences_fulltext))
         .route("/stats", get(api::get_stats))
         .route("/patterns/:keyword", get(api::get_pattern_detail))
         .route("/patterns/clear", post(api::clear_patterns))
@@ -101,10 +196,17 @@ async fn main() {
         .route("/memory/clear", delete(api::clear_memory))
         // Chat endpoints
         .route("/chat/send", post(api::send_chat_message))
+        .route("/chat/send/stream", post(api::stream_chat_message))
+        .route("/chat/send/events", post(api::send_chat_message_sse))
         .route("/chat/history/:session_id", get(api::get_chat_history))
+        .route("/chat/history/:session_id/latest", get(api::get_chat_latest))
+        .route("/chat/history/:session_id/before", get(api::get_chat_before))
+        .route("/chat/history/:session_id/after", get(api::get_chat_after))
         .route("/chat/sessions", get(api::list_chat_sessions))
         .route("/chat/sessions/:session_id", delete(api::clear_chat_session))
+        .route("/chat/ws/:session_id", get(api::chat_session_ws))
         .route("/chat/upload", post(api::upload_document))
+        .route("/chat/upload/file", post(api::upload_document_multipart))
         .route("/chat/export", get(api::export_chat_session))
         // API Learning CRUD endpoints
         .route("/api-learning/execute", post(api::execute_http_request))
@@ -112,8 +214,20 @@ async fn main() {
         .route("/api-learning/records/:id", get(api::get_learning_record_by_id))
         .route("/api-learning/records/:id", post(api::update_learning_record))
         .route("/api-learning/records/:id", delete(api::delete_learning_record))
+        .route("/api-learning/records/:id/replay", post(api::replay_learning_record))
+        .route("/api-learning/records/:id/versions", get(api::get_learning_record_versions))
+        .route("/api-learning/records/:id/restore", post(api::restore_learning_record))
         .route("/api-learning/search", get(api::search_learning_records))
+        .route(
+            "/api-learning/search/semantic",
+            get(api::search_learning_records_semantic),
+        )
         .route("/api-learning/clear", delete(api::clear_learning_records))
+        .route("/api-learning/purge", delete(api::purge_learning_records))
+        .layer(axum_middleware::from_fn_with_state(
+            app_state.clone(),
+            middleware::rate_limit_middleware,
+        ))
         .layer(axum_middleware::from_fn(middleware::auth_middleware));
 
     // Build public routes (no authentication)
@@ -128,9 +242,28 @@ async fn main() {
         .layer(CorsLayer::permissive())
         .with_state(app_state);
 
+    // Spawn background task to ping WebSocket subscribers and prune dead connections
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
+        loop {
+            interval.tick().await;
+            realtime.ping_and_prune(tokio::time::Duration::from_secs(90));
+        }
+    });
+
+    // Spawn background task to evict idle rate limit buckets so memory
+    // stays bounded regardless of how many distinct clients have connected
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(300));
+        loop {
+            interval.tick().await;
+            rate_limiter.evict_idle(tokio::time::Duration::from_secs(600));
+        }
+    });
+
     // Clone memory for background save task
     let memory_for_save = memory.clone();
-    
+
     // Spawn background task to periodically save memory
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
@@ -155,7 +288,7 @@ async fn main() {
     tracing::info!("   API listening on http://{}", addr);
     tracing::info!("   Use Bearer token in Authorization header");
     tracing::info!("\n📝 Example request:");
-    tracing::info!("   curl -H 'Authorization: Bearer {}' http://{}/health", config.bearer_token, addr);
+    tracing::info!("   curl -H 'Authorization: Bearer <your-token>' http://{}/health", addr);
     
     axum::serve(listener, app)
         .await
@@ -1,10 +1,31 @@
 use crate::experience::Experience;
+use crate::search::InvertedIndex;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io;
 use std::path::Path;
 use std::sync::{Arc, RwLock};
 
+/// An experience ranked by embedding similarity to a semantic search query
+#[derive(Debug, Clone)]
+pub struct SemanticMatch<'a> {
+    pub experience: &'a Experience,
+    pub score: f32,
+}
+
+/// Cosine similarity between two equal-length embedding vectors, in [-1.0, 1.0]
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
 /// Thread-safe memory storage for experiences
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Memory {
@@ -60,6 +81,97 @@ impl Memory {
             .collect()
     }
 
+    /// Search experiences by BM25-ranked full-text relevance with typo tolerance,
+    /// instead of plain substring matching. The index is built fresh from the
+    /// current experiences on every call.
+    pub fn search_bm25(&self, query: &str, top_n: usize) -> Vec<(&Experience, f32)> {
+        let mut index = InvertedIndex::with_params(1.2, 0.75);
+        for exp in &self.experiences {
+            index.index(&exp.id, &exp.content);
+        }
+
+        index
+            .search(query, top_n)
+            .into_iter()
+            .filter_map(|(id, score)| self.get_by_id(&id).map(|exp| (exp, score)))
+            .collect()
+    }
+
+    /// Search experiences by embedding similarity instead of substring matching.
+    /// Ranks the embeddings already stored on each experience (computed once
+    /// at `remember` time) against a precomputed `query_embedding`, skipping
+    /// experiences that have none, and returns the top `top_n` matches.
+    pub fn search_semantic(&self, query_embedding: &[f32], top_n: usize) -> Vec<SemanticMatch<'_>> {
+        let mut scored: Vec<SemanticMatch> = self
+            .experiences
+            .iter()
+            .filter_map(|exp| {
+                let embedding = exp.embedding.as_deref()?;
+                Some(SemanticMatch {
+                    experience: exp,
+                    score: cosine_similarity(query_embedding, embedding),
+                })
+            })
+            .collect();
+
+        scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(top_n);
+        scored
+    }
+
+    /// Resolve a cursor to a timestamp: either an experience id, or an RFC3339 timestamp
+    fn resolve_cursor(&self, cursor: &str) -> Option<DateTime<Utc>> {
+        if let Some(exp) = self.get_by_id(cursor) {
+            return Some(exp.timestamp);
+        }
+        DateTime::parse_from_rfc3339(cursor)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+
+    /// Experiences ordered by timestamp, windowed by `before`/`after` cursors
+    /// (each an experience id or RFC3339 timestamp, exclusive) and capped at
+    /// `limit`, CHATHISTORY-style. Returns the page plus cursors for the
+    /// adjacent pages so clients can scroll incrementally instead of
+    /// re-fetching the whole collection.
+    pub fn paginate_experiences(
+        &self,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<Experience>, Option<String>, Option<String>), String> {
+        let mut experiences: Vec<&Experience> = self.experiences.iter().collect();
+        experiences.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.id.cmp(&b.id)));
+
+        if let Some(cursor) = before {
+            let ts = self
+                .resolve_cursor(cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            experiences.retain(|e| e.timestamp < ts);
+        }
+        if let Some(cursor) = after {
+            let ts = self
+                .resolve_cursor(cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            experiences.retain(|e| e.timestamp > ts);
+        }
+
+        // With no `after` cursor (including no cursor at all), the page is
+        // the tail of the remaining window; an `after` cursor walks forward,
+        // so the page is the head instead.
+        let page: Vec<Experience> = if after.is_none() {
+            let skip = experiences.len().saturating_sub(limit);
+            experiences.split_off(skip).into_iter().cloned().collect()
+        } else {
+            experiences.truncate(limit);
+            experiences.into_iter().cloned().collect()
+        };
+
+        let next_cursor = page.last().map(|e| e.id.clone());
+        let prev_cursor = page.first().map(|e| e.id.clone());
+        Ok((page, next_cursor, prev_cursor))
+    }
+
     /// Display all experiences (for debugging)
     pub fn reflect(&self) {
         println!("\n📜 Reflection ({} experiences):", self.experiences.len());
@@ -1,10 +1,37 @@
+use crate::api::AppState;
 use crate::config::Config;
+use crate::rate_limit::{client_key, OperationClass};
 use axum::{
-    extract::Request,
-    http::{HeaderMap, StatusCode},
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Hash a token with HMAC-SHA256 (keyed with a fixed domain-separation string) and
+/// hex-encode it, so `BEARER_TOKEN` can hold a hash instead of the raw secret.
+pub fn hash_token(token: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(b"ai_core-bearer-token").expect("HMAC accepts any key length");
+    mac.update(token.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Fixed-time byte comparison so invalid tokens don't leak length/content via timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
 
 /// Bearer token authentication middleware
 pub async fn auth_middleware(
@@ -26,11 +53,12 @@ pub async fn auth_middleware(
     // Extract token
     let token = &auth_header[7..]; // Skip "Bearer "
 
-    // Load config to get expected token
+    // Load config to get the expected token hash
     let config = Config::from_env().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    // Verify token
-    if token != config.bearer_token {
+    // Verify token in constant time; BEARER_TOKEN holds the HMAC-SHA256 hash, not the raw secret
+    let presented_hash = hash_token(token);
+    if !constant_time_eq(presented_hash.as_bytes(), config.bearer_token.as_bytes()) {
         tracing::warn!("Invalid token attempt");
         return Err(StatusCode::UNAUTHORIZED);
     }
@@ -38,3 +66,27 @@ pub async fn auth_middleware(
     // Token is valid, continue
     Ok(next.run(request).await)
 }
+
+/// Per-client, per-operation-class rate limiting. Layered inside
+/// `auth_middleware` so it only sees already-authenticated traffic — a
+/// client can't use the 429 response to probe for valid API keys.
+pub async fn rate_limit_middleware(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Response {
+    let class = OperationClass::classify(request.method(), request.uri().path());
+    let client = client_key(&headers);
+
+    match state.rate_limiter.check(&client, class) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            let mut response = StatusCode::TOO_MANY_REQUESTS.into_response();
+            if let Ok(value) = HeaderValue::from_str(&retry_after.to_string()) {
+                response.headers_mut().insert(header::RETRY_AFTER, value);
+            }
+            response
+        }
+    }
+}
@@ -1,38 +1,214 @@
+use crate::llm::ConversationTurn;
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex as AsyncMutex;
 
 /// Ollama client for AI response generation
 pub struct OllamaClient {
     url: String,
     model: String,
+    /// Model used by `embed`, distinct from `model` (the chat model) since
+    /// embedding and chat models are rarely the same
+    embedding_model: String,
     enabled: bool,
+    /// Optional bearer token for Ollama deployments that sit behind their own auth
+    auth_token: Option<String>,
+    /// Context window size passed as `options.num_ctx` on `/api/chat` requests
+    num_ctx: Option<usize>,
+    /// Sampling temperature passed as `options.temperature`
+    temperature: Option<f32>,
+    /// Seed passed as `options.seed`, for deterministic generation
+    seed: Option<i64>,
+    /// Minimum spacing enforced between outgoing requests to the Ollama server
+    min_request_interval: Duration,
+    /// When the last request was sent, for client-side throttling
+    last_request_at: AsyncMutex<Option<Instant>>,
 }
 
-/// Ollama request structure
+/// A single role-tagged message in an `/api/chat` conversation
+#[derive(Debug, Clone, Serialize)]
+struct OllamaChatMessage {
+    role: String,
+    content: String,
+}
+
+/// Tunable generation options forwarded to Ollama's `/api/chat` endpoint
+#[derive(Debug, Serialize)]
+struct OllamaOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_ctx: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<i64>,
+}
+
+/// `/api/chat` request structure
 #[derive(Debug, Serialize)]
-struct OllamaRequest {
+struct OllamaChatRequest {
     model: String,
-    prompt: String,
+    messages: Vec<OllamaChatMessage>,
     stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<OllamaOptions>,
+    /// How long Ollama should keep this model resident after the request
+    /// completes (e.g. `"30m"`); only set on the preload warm-up request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<String>,
 }
 
-/// Ollama response structure
-#[derive(Debug, Deserialize)]
-struct OllamaResponse {
+/// `/api/chat` response message payload
+#[derive(Debug, Default, Deserialize)]
+struct OllamaChatMessagePayload {
     #[serde(default)]
-    response: String,
+    content: String,
+}
+
+/// `/api/chat` response structure (one object per line when streaming)
+#[derive(Debug, Default, Deserialize)]
+struct OllamaChatResponse {
+    #[serde(default)]
+    message: OllamaChatMessagePayload,
     #[serde(default)]
     done: bool,
     #[serde(default)]
     error: Option<String>,
 }
 
+/// Ollama embeddings request structure
+#[derive(Debug, Serialize)]
+struct OllamaEmbeddingRequest {
+    model: String,
+    prompt: String,
+}
+
+/// Ollama embeddings response structure
+#[derive(Debug, Deserialize)]
+struct OllamaEmbeddingResponse {
+    #[serde(default)]
+    embedding: Vec<f32>,
+}
+
 impl OllamaClient {
     /// Create new Ollama client from config
     pub fn new(url: String, model: String, enabled: bool) -> Self {
         Self {
             url,
+            embedding_model: model.clone(),
             model,
             enabled,
+            auth_token: None,
+            num_ctx: None,
+            temperature: None,
+            seed: None,
+            min_request_interval: Duration::ZERO,
+            last_request_at: AsyncMutex::new(None),
+        }
+    }
+
+    /// Use a dedicated model for `embed` instead of defaulting to the chat model
+    pub fn with_embedding_model(mut self, embedding_model: String) -> Self {
+        self.embedding_model = embedding_model;
+        self
+    }
+
+    /// Attach a bearer token for Ollama deployments that sit behind their own auth
+    pub fn with_auth_token(mut self, auth_token: Option<String>) -> Self {
+        self.auth_token = auth_token;
+        self
+    }
+
+    /// Set the context window size (`options.num_ctx`) sent with every `/api/chat` request
+    pub fn with_num_ctx(mut self, num_ctx: Option<usize>) -> Self {
+        self.num_ctx = num_ctx;
+        self
+    }
+
+    /// Set the sampling temperature (`options.temperature`) sent with every `/api/chat` request
+    pub fn with_temperature(mut self, temperature: Option<f32>) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// Set the generation seed (`options.seed`) sent with every `/api/chat` request
+    pub fn with_seed(mut self, seed: Option<i64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Cap outgoing requests to the Ollama server at `max_requests_per_second`,
+    /// deriving the minimum spacing between requests internally. `0.0` disables throttling.
+    pub fn with_max_requests_per_second(mut self, max_requests_per_second: f32) -> Self {
+        self.min_request_interval = if max_requests_per_second > 0.0 {
+            Duration::from_secs_f32(1.0 / max_requests_per_second)
+        } else {
+            Duration::ZERO
+        };
+        self
+    }
+
+    /// Sleep, if needed, so requests to Ollama are spaced at least
+    /// `min_request_interval` apart
+    async fn throttle(&self) {
+        if self.min_request_interval.is_zero() {
+            return;
+        }
+
+        let mut last_request_at = self.last_request_at.lock().await;
+        if let Some(last) = *last_request_at {
+            let elapsed = last.elapsed();
+            if elapsed < self.min_request_interval {
+                tokio::time::sleep(self.min_request_interval - elapsed).await;
+            }
+        }
+        *last_request_at = Some(Instant::now());
+    }
+
+    fn chat_options(&self) -> Option<OllamaOptions> {
+        if self.num_ctx.is_none() && self.temperature.is_none() && self.seed.is_none() {
+            return None;
+        }
+        Some(OllamaOptions {
+            num_ctx: self.num_ctx,
+            temperature: self.temperature,
+            seed: self.seed,
+        })
+    }
+
+    /// Build a role-tagged message list: an optional system message carrying
+    /// memory context, the prior conversation turns (oldest first), and
+    /// finally the new user message.
+    fn build_messages(
+        &self,
+        user_input: &str,
+        context: &[String],
+        history: &[ConversationTurn],
+    ) -> Vec<OllamaChatMessage> {
+        let mut messages = Vec::new();
+        if !context.is_empty() {
+            messages.push(OllamaChatMessage {
+                role: "system".to_string(),
+                content: format!("Context from memory:\n{}", context.join("\n")),
+            });
+        }
+        for turn in history {
+            messages.push(OllamaChatMessage {
+                role: turn.role.clone(),
+                content: turn.content.clone(),
+            });
+        }
+        messages.push(OllamaChatMessage {
+            role: "user".to_string(),
+            content: user_input.to_string(),
+        });
+        messages
+    }
+
+    /// Apply the configured bearer token to a request builder, if any
+    fn authorize(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.auth_token {
+            Some(token) => builder.bearer_auth(token),
+            None => builder,
         }
     }
 
@@ -41,26 +217,60 @@ impl OllamaClient {
         self.enabled
     }
 
-    /// Generate AI response using Ollama
+    /// Base URL of the Ollama server
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Configured model name
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Generate AI response using Ollama's `/api/chat` endpoint
     pub async fn generate(&self, prompt: &str) -> Result<String, String> {
+        self.chat(vec![OllamaChatMessage {
+            role: "user".to_string(),
+            content: prompt.to_string(),
+        }])
+        .await
+    }
+
+    /// Generate response with context from memory and prior conversation
+    /// turns, tagged as a system message followed by the role-tagged history
+    pub async fn generate_with_context(
+        &self,
+        user_input: &str,
+        context: &[String],
+        history: &[ConversationTurn],
+    ) -> Result<String, String> {
+        self.chat(self.build_messages(user_input, context, history)).await
+    }
+
+    /// Send role-tagged messages to Ollama's `/api/chat` endpoint and return the
+    /// assistant's reply
+    async fn chat(&self, messages: Vec<OllamaChatMessage>) -> Result<String, String> {
         if !self.enabled {
             return Err("Ollama is disabled. Set OLLAMA_ENABLED=true in .env".to_string());
         }
 
+        self.throttle().await;
+
         let client = reqwest::Client::new();
-        let endpoint = format!("{}/api/generate", self.url);
+        let endpoint = format!("{}/api/chat", self.url);
 
-        let request_body = OllamaRequest {
+        let request_body = OllamaChatRequest {
             model: self.model.clone(),
-            prompt: prompt.to_string(),
+            messages,
             stream: false,
+            options: self.chat_options(),
+            keep_alive: None,
         };
 
         tracing::debug!("Sending request to Ollama: {}", endpoint);
         tracing::debug!("Model: {}", self.model);
-        tracing::debug!("Prompt length: {} chars", prompt.len());
 
-        match client.post(&endpoint)
+        match self.authorize(client.post(&endpoint))
             .json(&request_body)
             .timeout(std::time::Duration::from_secs(120))
             .send()
@@ -76,18 +286,18 @@ impl OllamaClient {
                     return Err(format!("Ollama API error ({}): {}", status, error_text));
                 }
 
-                match response.json::<OllamaResponse>().await {
+                match response.json::<OllamaChatResponse>().await {
                     Ok(ollama_response) => {
                         if let Some(error) = ollama_response.error {
                             return Err(format!("Ollama error: {}", error));
                         }
 
-                        if ollama_response.response.is_empty() {
+                        if ollama_response.message.content.is_empty() {
                             return Err("Ollama returned empty response".to_string());
                         }
 
-                        tracing::debug!("Ollama response length: {} chars", ollama_response.response.len());
-                        Ok(ollama_response.response)
+                        tracing::debug!("Ollama response length: {} chars", ollama_response.message.content.len());
+                        Ok(ollama_response.message.content)
                     }
                     Err(e) => {
                         Err(format!("Failed to parse Ollama response: {}", e))
@@ -100,25 +310,136 @@ impl OllamaClient {
         }
     }
 
-    /// Generate response with context from memory
-    pub async fn generate_with_context(
+    /// Generate a response with context as a stream of incremental text chunks.
+    /// Ollama's `/api/chat` with `stream: true` emits newline-delimited JSON
+    /// objects; each line's `message.content` field holds the next token fragment.
+    pub async fn generate_stream_with_context(
         &self,
         user_input: &str,
         context: &[String],
-    ) -> Result<String, String> {
-        let context_text = if context.is_empty() {
-            String::from("No context available.")
-        } else {
-            format!("Context from memory:\n{}", context.join("\n"))
-        };
+        history: &[ConversationTurn],
+    ) -> Result<impl futures_util::Stream<Item = Result<String, String>>, String> {
+        if !self.enabled {
+            return Err("Ollama is disabled. Set OLLAMA_ENABLED=true in .env".to_string());
+        }
 
-        let prompt = format!(
-            "{}\n\nUser question: {}\n\nPlease provide a helpful response based on the context above.",
-            context_text,
-            user_input
-        );
+        self.throttle().await;
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/api/chat", self.url);
+
+        let response = self
+            .authorize(client.post(&endpoint))
+            .json(&OllamaChatRequest {
+                model: self.model.clone(),
+                messages: self.build_messages(user_input, context, history),
+                stream: true,
+                options: self.chat_options(),
+                keep_alive: None,
+            })
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Ollama API error: {}", response.status()));
+        }
+
+        use futures_util::StreamExt;
+        use tokio::io::AsyncBufReadExt;
+        use tokio_stream::wrappers::LinesStream;
+        use tokio_util::io::StreamReader;
+
+        let byte_stream = response
+            .bytes_stream()
+            .map(|r| r.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)));
+        let reader = StreamReader::new(byte_stream);
+        let lines = LinesStream::new(reader.lines());
+
+        let token_stream = lines.map(|line| {
+            let line = line.map_err(|e| format!("Stream read error: {}", e))?;
+            if line.trim().is_empty() {
+                return Ok(String::new());
+            }
+            let chunk: OllamaChatResponse = serde_json::from_str(&line)
+                .map_err(|e| format!("Failed to parse Ollama stream chunk: {}", e))?;
+            Ok(chunk.message.content)
+        });
+
+        Ok(token_stream)
+    }
+
+    /// Ask Ollama to load the configured model into memory with an empty
+    /// message list, so the first real request doesn't pay cold-start latency.
+    pub async fn preload_model(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Err("Ollama is disabled. Set OLLAMA_ENABLED=true in .env".to_string());
+        }
+
+        self.throttle().await;
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/api/chat", self.url);
+
+        let response = self
+            .authorize(client.post(&endpoint))
+            .json(&OllamaChatRequest {
+                model: self.model.clone(),
+                messages: vec![],
+                stream: false,
+                options: self.chat_options(),
+                // Keep the model resident well past Ollama's default idle
+                // timeout so later chat requests don't pay cold-start latency
+                keep_alive: Some("30m".to_string()),
+            })
+            .timeout(std::time::Duration::from_secs(120))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Ollama API error: {}", response.status()));
+        }
+
+        Ok(())
+    }
+
+    /// Compute an embedding vector for `text` via Ollama's `/api/embeddings` endpoint
+    pub async fn embed(&self, text: &str) -> Result<Vec<f32>, String> {
+        if !self.enabled {
+            return Err("Ollama is disabled. Set OLLAMA_ENABLED=true in .env".to_string());
+        }
+
+        self.throttle().await;
+
+        let client = reqwest::Client::new();
+        let endpoint = format!("{}/api/embeddings", self.url);
+
+        let response = self
+            .authorize(client.post(&endpoint))
+            .json(&OllamaEmbeddingRequest {
+                model: self.embedding_model.clone(),
+                prompt: text.to_string(),
+            })
+            .timeout(std::time::Duration::from_secs(30))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Ollama embeddings API error: {}", response.status()));
+        }
+
+        let parsed: OllamaEmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse embeddings response: {}", e))?;
+
+        if parsed.embedding.is_empty() {
+            return Err("Ollama returned an empty embedding".to_string());
+        }
 
-        self.generate(&prompt).await
+        Ok(parsed.embedding)
     }
 
     /// Check if Ollama server is available
@@ -130,7 +451,7 @@ impl OllamaClient {
         let client = reqwest::Client::new();
         let endpoint = format!("{}/api/tags", self.url);
 
-        match client.get(&endpoint)
+        match self.authorize(client.get(&endpoint))
             .timeout(std::time::Duration::from_secs(5))
             .send()
             .await
@@ -156,7 +477,7 @@ impl OllamaClient {
         let client = reqwest::Client::new();
         let endpoint = format!("{}/api/tags", self.url);
 
-        match client.get(&endpoint)
+        match self.authorize(client.get(&endpoint))
             .timeout(std::time::Duration::from_secs(10))
             .send()
             .await
@@ -1,6 +1,6 @@
 use crate::experience::Experience;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Pattern data structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,10 +10,75 @@ pub struct Pattern {
     pub experience_ids: Vec<String>,
 }
 
-/// Recognizes and tracks patterns in experiences
+/// Coarse entity category, classified with capitalization heuristics plus a gazetteer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntityType {
+    Person,
+    Location,
+    Organization,
+    Other,
+}
+
+/// A salient entity detected across experiences
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entity {
+    pub text: String,
+    pub entity_type: EntityType,
+    pub frequency: usize,
+    /// Normalized frequency across all detected entity mentions
+    pub salience: f32,
+}
+
+/// Small gazetteer of well-known locations and organizations (lowercase)
+const LOCATIONS: &[&str] = &[
+    "jakarta", "bandung", "surabaya", "bali", "jogja", "yogyakarta", "medan", "indonesia",
+    "london", "paris", "tokyo", "singapore", "beijing", "washington",
+];
+const ORGANIZATIONS: &[&str] = &[
+    "google", "microsoft", "apple", "amazon", "meta", "openai", "ollama", "pt", "cv",
+    "universitas", "kementerian", "pemerintah",
+];
+
+/// Term weights for the positive/negative sentiment lexicon. The lexicon is
+/// fixed at compile time; it is not loaded from configuration.
+const POSITIVE_TERMS: &[(&str, f32)] = &[
+    ("bagus", 1.0), ("baik", 1.0), ("senang", 1.0), ("suka", 1.0), ("terima", 0.5),
+    ("kasih", 0.5), ("mantap", 1.0), ("good", 1.0), ("great", 1.0), ("happy", 1.0),
+    ("love", 1.0), ("thanks", 1.0), ("thank", 1.0), ("helpful", 1.0),
+];
+const NEGATIVE_TERMS: &[(&str, f32)] = &[
+    ("buruk", 1.0), ("jelek", 1.0), ("marah", 1.0), ("benci", 1.0), ("error", 1.0),
+    ("bahaya", 1.0), ("rusak", 1.0), ("bad", 1.0), ("hate", 1.0), ("angry", 1.0),
+    ("danger", 1.0), ("fail", 1.0), ("gagal", 1.0),
+];
+
+fn classify_entity(word: &str) -> EntityType {
+    let lower = word.to_lowercase();
+    if LOCATIONS.contains(&lower.as_str()) {
+        EntityType::Location
+    } else if ORGANIZATIONS.contains(&lower.as_str()) {
+        EntityType::Organization
+    } else if word.chars().next().map(|c| c.is_uppercase()).unwrap_or(false) {
+        EntityType::Person
+    } else {
+        EntityType::Other
+    }
+}
+
+/// Recognizes and tracks patterns, entities, and sentiment in experiences
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PatternRecognizer {
     patterns: HashMap<String, Pattern>,
+    entities: HashMap<String, Entity>,
+    total_entity_mentions: usize,
+    /// Per-experience sentiment score in [-1.0, 1.0]
+    sentiments: HashMap<String, f32>,
+    /// Running sum of |sentiment| across all analyzed experiences
+    sentiment_magnitude: f32,
+    /// Experience ids already folded into the counters above, so re-analyzing
+    /// the same experience (e.g. re-retrieved as chat context) is a no-op
+    /// instead of inflating frequencies/magnitudes on every view
+    analyzed_ids: HashSet<String>,
 }
 
 impl Default for PatternRecognizer {
@@ -27,11 +92,22 @@ impl PatternRecognizer {
     pub fn new() -> Self {
         Self {
             patterns: HashMap::new(),
+            entities: HashMap::new(),
+            total_entity_mentions: 0,
+            sentiments: HashMap::new(),
+            sentiment_magnitude: 0.0,
+            analyzed_ids: HashSet::new(),
         }
     }
 
-    /// Analyze an experience and extract patterns
+    /// Analyze an experience and extract patterns, entities, and sentiment.
+    /// A no-op if this experience id has already been analyzed, so repeatedly
+    /// retrieving the same experience as chat context doesn't inflate counts.
     pub fn analyze(&mut self, exp: &Experience) {
+        if !self.analyzed_ids.insert(exp.id.clone()) {
+            return;
+        }
+
         let words: Vec<String> = exp
             .content
             .split_whitespace()
@@ -39,7 +115,7 @@ impl PatternRecognizer {
             .filter(|w| w.len() > 2) // Skip very short words
             .collect();
 
-        for word in words {
+        for word in &words {
             self.patterns
                 .entry(word.clone())
                 .and_modify(|p| {
@@ -49,11 +125,72 @@ impl PatternRecognizer {
                     }
                 })
                 .or_insert_with(|| Pattern {
-                    keyword: word,
+                    keyword: word.clone(),
                     frequency: 1,
                     experience_ids: vec![exp.id.clone()],
                 });
         }
+
+        self.extract_entities(exp);
+        self.score_sentiment(exp, &words);
+    }
+
+    /// Classify salient tokens into PERSON/LOCATION/ORGANIZATION/OTHER and track salience
+    fn extract_entities(&mut self, exp: &Experience) {
+        let candidates: Vec<String> = exp
+            .content
+            .split_whitespace()
+            .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()).to_string())
+            .filter(|w| w.len() > 2)
+            .collect();
+
+        for word in candidates {
+            let lower = word.to_lowercase();
+            let is_gazetteer_hit = LOCATIONS.contains(&lower.as_str()) || ORGANIZATIONS.contains(&lower.as_str());
+            let is_capitalized = word.chars().next().map(|c| c.is_uppercase()).unwrap_or(false);
+            if !is_gazetteer_hit && !is_capitalized {
+                continue; // not a salient entity candidate
+            }
+
+            let entity_type = classify_entity(&word);
+            self.total_entity_mentions += 1;
+            self.entities
+                .entry(lower.clone())
+                .and_modify(|e| e.frequency += 1)
+                .or_insert_with(|| Entity {
+                    text: word.clone(),
+                    entity_type,
+                    frequency: 1,
+                    salience: 0.0,
+                });
+        }
+
+        // Recompute normalized salience now that total mentions changed
+        let total = self.total_entity_mentions.max(1) as f32;
+        for entity in self.entities.values_mut() {
+            entity.salience = entity.frequency as f32 / total;
+        }
+    }
+
+    /// Sum configured lexicon weights over tokens, normalized by token count, clamped to [-1, 1]
+    fn score_sentiment(&mut self, exp: &Experience, words: &[String]) {
+        if words.is_empty() {
+            self.sentiments.insert(exp.id.clone(), 0.0);
+            return;
+        }
+
+        let mut score = 0.0f32;
+        for word in words {
+            if let Some((_, weight)) = POSITIVE_TERMS.iter().find(|(term, _)| term == word) {
+                score += weight;
+            } else if let Some((_, weight)) = NEGATIVE_TERMS.iter().find(|(term, _)| term == word) {
+                score -= weight;
+            }
+        }
+
+        let normalized = (score / words.len() as f32).clamp(-1.0, 1.0);
+        self.sentiment_magnitude += normalized.abs();
+        self.sentiments.insert(exp.id.clone(), normalized);
     }
 
     /// Get all recognized patterns
@@ -73,6 +210,27 @@ impl PatternRecognizer {
         patterns.into_iter().take(n).collect()
     }
 
+    /// Get top N entities by salience
+    pub fn get_top_entities(&self, n: usize) -> Vec<&Entity> {
+        let mut entities: Vec<&Entity> = self.entities.values().collect();
+        entities.sort_by(|a, b| b.salience.partial_cmp(&a.salience).unwrap_or(std::cmp::Ordering::Equal));
+        entities.into_iter().take(n).collect()
+    }
+
+    /// Sentiment score in [-1.0, 1.0] for a previously analyzed experience
+    pub fn sentiment_of(&self, experience_id: &str) -> Option<f32> {
+        self.sentiments.get(experience_id).copied()
+    }
+
+    /// Average sentiment magnitude across all analyzed experiences
+    pub fn average_sentiment_magnitude(&self) -> f32 {
+        if self.sentiments.is_empty() {
+            0.0
+        } else {
+            self.sentiment_magnitude / self.sentiments.len() as f32
+        }
+    }
+
     /// Display recognized patterns
     pub fn show_patterns(&self) {
         println!("\n🔍 Recognized patterns ({} keywords):", self.patterns.len());
@@ -87,8 +245,13 @@ impl PatternRecognizer {
         }
     }
 
-    /// Clear all patterns
+    /// Clear all patterns, entities, and sentiment data
     pub fn clear(&mut self) {
         self.patterns.clear();
+        self.entities.clear();
+        self.total_entity_mentions = 0;
+        self.sentiments.clear();
+        self.sentiment_magnitude = 0.0;
+        self.analyzed_ids.clear();
     }
 }
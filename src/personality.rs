@@ -1,6 +1,56 @@
 use crate::memory::Memory;
 use crate::pattern::PatternRecognizer;
+use crate::qlearning::{Action, QLearner, State};
+use crate::sentiment::SentimentLexicon;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Words that read as hostile toward the assistant specifically, a stronger
+/// signal than the caution-triggering warning/error words below
+const HOSTILE_TERMS: &[&str] = &["benci", "bodoh", "hate", "stupid", "bangsat"];
+
+/// `(upper bound inclusive, label)` pairs for `attitude_label`, checked in
+/// ascending order. Retuning or localizing the tiers is a data change here,
+/// not a new `if` branch, since the keyword set elsewhere in this module
+/// already mixes Indonesian and English and more languages may follow.
+const AFFINITY_TIERS: &[(i32, &str)] = &[
+    (-52, "bloodfeud"),
+    (-21, "loathing"),
+    (-11, "hatred"),
+    (10, "neutral"),
+    (50, "friendly"),
+    (i32::MAX, "admiration"),
+];
+
+/// Default per-trait decay rate `k` in `decay`'s
+/// `trait += (baseline - trait) * (1 - (-k*elapsed).exp())`, chosen so a
+/// trait pulled to an extreme visibly relaxes within a handful of turns
+/// without washing out a single strong reaction immediately
+const DEFAULT_DECAY_RATE: f32 = 0.15;
+const DEFAULT_BASELINE: f32 = 0.5;
+
+/// Baseline each trait drifts back toward over time, and the per-trait
+/// rates it drifts at, absent fresh input. A field on `Personality` (not a
+/// module constant) so a persisted personality keeps decaying with the
+/// configuration it was created under even if the defaults change later.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DecayConfig {
+    pub baseline: f32,
+    pub curiosity_rate: f32,
+    pub happiness_rate: f32,
+    pub caution_rate: f32,
+}
+
+impl Default for DecayConfig {
+    fn default() -> Self {
+        Self {
+            baseline: DEFAULT_BASELINE,
+            curiosity_rate: DEFAULT_DECAY_RATE,
+            happiness_rate: DEFAULT_DECAY_RATE,
+            caution_rate: DEFAULT_DECAY_RATE,
+        }
+    }
+}
 
 /// AI personality traits that evolve over time
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,6 +58,28 @@ pub struct Personality {
     pub curiosity: f32,  // 0.0 - 1.0, how curious the AI is
     pub happiness: f32,  // 0.0 - 1.0, mood level
     pub caution: f32,    // 0.0 - 1.0, carefulness level
+    /// How the AI feels toward this specific user, -100..=100, as opposed to
+    /// `happiness` which tracks its general mood
+    pub affinity: i32,
+    /// Baseline and per-trait rates `decay` pulls traits back toward, so a
+    /// persisted personality reconstructed from `Memory` keeps drifting
+    /// correctly instead of resetting to hardcoded defaults
+    #[serde(default)]
+    pub decay: DecayConfig,
+    /// Optional Q-learning policy over response styles, attached via
+    /// `with_qlearner`. Not serialized: the learned table lives in the
+    /// `QLearner` itself, not in any one `Personality` snapshot.
+    #[serde(skip)]
+    qlearner: Option<Arc<QLearner>>,
+    /// The (state, action) chosen by the last `choose_style` call, held so a
+    /// later `learn` call can credit it once a reward is observed.
+    #[serde(skip)]
+    pending_choice: Option<(State, Action)>,
+    /// Token-weight lexicon `update` scores input against. Defaults to
+    /// `SentimentLexicon::default_pack`; attach a configured set of language
+    /// packs via `with_lexicon`.
+    #[serde(skip)]
+    lexicon: Arc<SentimentLexicon>,
 }
 
 impl Default for Personality {
@@ -23,36 +95,116 @@ impl Personality {
             curiosity: 0.5,
             happiness: 0.5,
             caution: 0.5,
+            affinity: 0,
+            decay: DecayConfig::default(),
+            qlearner: None,
+            pending_choice: None,
+            lexicon: Arc::new(SentimentLexicon::default()),
         }
     }
 
-    /// Update personality based on new input
-    pub fn update(&mut self, input: &str, _mem: &Memory, _patterns: &PatternRecognizer) {
+    /// Opt into the Q-learning response-style policy backed by `qlearner`
+    pub fn with_qlearner(mut self, qlearner: Arc<QLearner>) -> Self {
+        self.qlearner = Some(qlearner);
+        self
+    }
+
+    /// Score input against `lexicon` instead of the built-in default pack
+    pub fn with_lexicon(mut self, lexicon: Arc<SentimentLexicon>) -> Self {
+        self.lexicon = lexicon;
+        self
+    }
+
+    /// Exponentially pull each trait back toward `decay.baseline` based on
+    /// how many turns have passed without fresh input, so moods fade
+    /// naturally instead of getting stuck at an extreme after a handful of
+    /// matching messages. `elapsed_turns` is usually 1 (one call per
+    /// `update`), but a caller catching up a personality that was persisted
+    /// a while ago can pass a larger value to fast-forward the drift.
+    pub fn decay(&mut self, elapsed_turns: u32) {
+        let elapsed = elapsed_turns as f32;
+        let baseline = self.decay.baseline;
+        let pull = |value: f32, rate: f32| value + (baseline - value) * (1.0 - (-rate * elapsed).exp());
+
+        self.curiosity = pull(self.curiosity, self.decay.curiosity_rate).clamp(0.0, 1.0);
+        self.happiness = pull(self.happiness, self.decay.happiness_rate).clamp(0.0, 1.0);
+        self.caution = pull(self.caution, self.decay.caution_rate).clamp(0.0, 1.0);
+    }
+
+    /// Update personality based on new input: first let traits relax one
+    /// turn's worth toward baseline, then tokenize the input against
+    /// `lexicon` and accumulate the matched tokens' weights into each trait,
+    /// rather than checking for a fixed set of hardcoded substrings
+    pub fn update(&mut self, input: &str, _mem: &Memory, patterns: &PatternRecognizer) {
+        self.decay(1);
+
         let input_lower = input.to_lowercase();
-        
-        // Increase happiness on positive words
-        if input_lower.contains("halo") || input_lower.contains("hello") || input_lower.contains("terima kasih") {
-            self.happiness += 0.1;
+        let score = self.lexicon.score(input);
+
+        // Valence (greetings, thanks, ...) raises happiness and affinity
+        if score.valence > 0.0 {
+            self.happiness += score.valence * 0.1;
+            self.affinity += 3;
         }
-        
-        // Increase curiosity on questions
-        if input_lower.contains("apa") || input_lower.contains("mengapa") || input_lower.contains("bagaimana") {
-            self.curiosity += 0.1;
+
+        // Interrogative tokens (question words) raise curiosity
+        if score.interrogative > 0.0 {
+            self.curiosity += score.interrogative * 0.1;
         }
-        
-        // Increase caution on negative/warning words
-        if input_lower.contains("bahaya") || input_lower.contains("error") || input_lower.contains("warning") {
-            self.caution += 0.2;
+
+        // Threat tokens (danger/error/warning) raise caution; these also
+        // cost a little affinity since they're associated with things going wrong
+        if score.threat > 0.0 {
+            self.caution += score.threat * 0.2;
+            self.affinity -= 2;
         }
 
-        // Clamp values between 0.0 and 1.0
+        // Outright hostility toward the assistant costs affinity directly.
+        // A hostile term that keeps recurring (tracked via `patterns`) reads
+        // as a grudge rather than a one-off remark, so it costs more. This
+        // stays its own lexicon since it's about the relationship with this
+        // user, not general trait mood.
+        for term in HOSTILE_TERMS {
+            if input_lower.contains(term) {
+                let repeated = patterns.get_pattern(term).map(|p| p.frequency).unwrap_or(0) > 2;
+                self.affinity -= if repeated { 10 } else { 5 };
+            }
+        }
+
+        // Clamp values to their respective ranges
         self.happiness = self.happiness.clamp(0.0, 1.0);
         self.curiosity = self.curiosity.clamp(0.0, 1.0);
         self.caution = self.caution.clamp(0.0, 1.0);
+        self.affinity = self.affinity.clamp(-100, 100);
+    }
+
+    /// Label the current `affinity` score with a descriptive relationship tier
+    pub fn attitude_label(&self) -> &str {
+        AFFINITY_TIERS
+            .iter()
+            .find(|(upper, _)| self.affinity <= *upper)
+            .map(|(_, label)| *label)
+            .unwrap_or("neutral")
     }
 
-    /// Influence response based on current personality state
-    pub fn influence_response(&self, reply: &str) -> String {
+    /// Influence response based on current personality state and the
+    /// relationship with this specific user: at the extremes, how the AI
+    /// feels about the user overrides its general mood, since the
+    /// relationship matters more there than whatever else is going on.
+    /// `style`, if given (from `choose_style`), picks the tone instead of
+    /// the mood thresholds below — the learned policy takes over once it
+    /// has an opinion, but the attitude override still comes first.
+    pub fn influence_response(&self, reply: &str, style: Option<Action>) -> String {
+        match self.attitude_label() {
+            "bloodfeud" | "loathing" => return format!("😒 {}", reply),
+            "admiration" => return format!("💛 {}", reply),
+            _ => {}
+        }
+
+        if let Some(action) = style {
+            return action.tone(reply);
+        }
+
         if self.happiness > 0.7 {
             format!("😊 {}", reply)
         } else if self.curiosity > 0.7 {
@@ -64,6 +216,33 @@ impl Personality {
         }
     }
 
+    /// Pick a response style via the Q-learning policy (epsilon-greedy over
+    /// the discretized personality state), remembering the (state, action)
+    /// pair so a later `learn` call can credit it. Returns `None` if no
+    /// `QLearner` has been attached via `with_qlearner`.
+    pub fn choose_style(&mut self) -> Option<Action> {
+        let qlearner = self.qlearner.as_ref()?;
+        let state = State::from_traits(self.curiosity, self.happiness, self.caution);
+        let action = qlearner.choose_action(state);
+        self.pending_choice = Some((state, action));
+        Some(action)
+    }
+
+    /// Apply the Q-learning update for the (state, action) chosen by the
+    /// last `choose_style` call, crediting it with `reward` (positive for
+    /// an approving follow-up, negative for a frustrated one). A no-op if
+    /// no style has been chosen yet or no `QLearner` is attached.
+    pub fn learn(&mut self, reward: f32) {
+        let Some(qlearner) = self.qlearner.as_ref() else {
+            return;
+        };
+        let Some((state, action)) = self.pending_choice.take() else {
+            return;
+        };
+        let next_state = State::from_traits(self.curiosity, self.happiness, self.caution);
+        qlearner.learn(state, action, reward, next_state);
+    }
+
     /// Get the dominant trait
     pub fn dominant_trait(&self) -> &str {
         if self.happiness >= self.curiosity && self.happiness >= self.caution {
@@ -74,4 +253,49 @@ impl Personality {
             "cautious"
         }
     }
+
+    /// Weighted composite of the traits into a single comparable 0-100
+    /// stability score: happiness raises it, and caution is stabilizing only
+    /// near its middle band — caution at either extreme (recklessness or
+    /// paralysis) reads as anxious rather than careful, so it's penalized.
+    pub fn mental_stability(&self) -> u8 {
+        let caution_penalty = ((self.caution - 0.5).abs() * 2.0).min(1.0);
+        let raw = self.happiness * 0.6 + (1.0 - caution_penalty) * 0.4;
+        get_rating(raw, 0.0, 1.0, [78, 64, 49, 35])
+    }
+
+    /// Label `mental_stability`'s score with a descriptive emotional tier
+    pub fn emotional_tier(&self) -> &str {
+        match self.mental_stability() {
+            100 => "very stable",
+            s if s >= 78 => "stable",
+            s if s >= 64 => "steady",
+            s if s >= 49 => "volatile",
+            _ => "unstable",
+        }
+    }
+}
+
+/// Clamp `value` into `[min, max]`, rescale it linearly onto a 0..100 scale,
+/// then snap that onto one of five discrete bands defined by the four
+/// descending percentile breakpoints in `t` (e.g. `[78, 64, 49, 35]`),
+/// returning the band's representative score. Bucketing into fixed bands
+/// instead of returning the raw interpolation makes the result a stable,
+/// comparable rating, and retuning the tiers is a change to `t`, not new code.
+pub fn get_rating(value: f32, min: f32, max: f32, t: [u8; 4]) -> u8 {
+    let clamped = value.clamp(min, max);
+    let span = (max - min).max(f32::EPSILON);
+    let normalized = ((clamped - min) / span * 100.0).round().clamp(0.0, 100.0) as u8;
+
+    if normalized >= t[0] {
+        100
+    } else if normalized >= t[1] {
+        t[0]
+    } else if normalized >= t[2] {
+        t[1]
+    } else if normalized >= t[3] {
+        t[2]
+    } else {
+        0
+    }
 }
@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+/// Learning rate and discount factor for the Q-learning update
+/// `Q(s,a) <- Q(s,a) + ALPHA * (r + GAMMA * max_a' Q(s',a') - Q(s,a))`
+const ALPHA: f32 = 0.1;
+const GAMMA: f32 = 0.9;
+/// Chance of picking a random action instead of the best-known one, so the
+/// policy keeps exploring instead of locking onto an early local optimum
+const EPSILON: f32 = 0.1;
+
+/// One of three buckets a continuous `Personality` trait is quantized into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Bucket {
+    Low,
+    Med,
+    High,
+}
+
+impl Bucket {
+    fn of(value: f32) -> Self {
+        if value < 0.34 {
+            Bucket::Low
+        } else if value < 0.67 {
+            Bucket::Med
+        } else {
+            Bucket::High
+        }
+    }
+}
+
+/// A discretized `Personality` snapshot: each trait quantized into
+/// low/med/high, giving 3^3 = 27 distinct states
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct State {
+    pub curiosity: Bucket,
+    pub happiness: Bucket,
+    pub caution: Bucket,
+}
+
+impl State {
+    pub fn from_traits(curiosity: f32, happiness: f32, caution: f32) -> Self {
+        Self {
+            curiosity: Bucket::of(curiosity),
+            happiness: Bucket::of(happiness),
+            caution: Bucket::of(caution),
+        }
+    }
+}
+
+/// A response style the policy can choose for the next reply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Cheerful,
+    Inquisitive,
+    Cautious,
+    Neutral,
+}
+
+impl Action {
+    pub const ALL: [Action; 4] = [
+        Action::Cheerful,
+        Action::Inquisitive,
+        Action::Cautious,
+        Action::Neutral,
+    ];
+
+    /// Tone this action applies to a reply, mirroring the emoji tones
+    /// `Personality::influence_response` already uses for mood
+    pub fn tone(&self, reply: &str) -> String {
+        match self {
+            Action::Cheerful => format!("😊 {}", reply),
+            Action::Inquisitive => format!("🤔 {}", reply),
+            Action::Cautious => format!("⚠️ {}", reply),
+            Action::Neutral => reply.to_string(),
+        }
+    }
+}
+
+/// One flattened `(state, action) -> value` row, since `serde_json` object
+/// keys must be strings and `(State, Action)` isn't one
+#[derive(Debug, Serialize, Deserialize)]
+struct QEntry {
+    state: State,
+    action: Action,
+    value: f32,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct PersistedTable {
+    entries: Vec<QEntry>,
+}
+
+/// Minimal xorshift PRNG seeded from the system clock. Epsilon-greedy
+/// exploration doesn't need cryptographic randomness, so this avoids an
+/// external `rand` dependency for something this low-stakes.
+fn next_random_unit() -> f32 {
+    static STATE: AtomicU64 = AtomicU64::new(0);
+
+    let mut x = STATE.load(Ordering::Relaxed);
+    if x == 0 {
+        x = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x2545_F491_4F6C_DD1D)
+            | 1;
+    }
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    STATE.store(x, Ordering::Relaxed);
+    (x % 1_000_000) as f32 / 1_000_000.0
+}
+
+/// Persists a Q-learning table mapping `(discretized personality state,
+/// response style) -> value` to disk, mirroring `LearningRecordStore`'s
+/// load/persist pattern. Shared across requests so the response-style
+/// policy keeps adapting across sessions instead of resetting every call.
+pub struct QLearner {
+    path: PathBuf,
+    table: RwLock<HashMap<(State, Action), f32>>,
+}
+
+impl QLearner {
+    /// Load a Q-table from `path` if it exists, otherwise start with all values at zero
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let table = Self::load(&path).unwrap_or_default();
+        Self {
+            path,
+            table: RwLock::new(table),
+        }
+    }
+
+    fn load(path: &PathBuf) -> Option<HashMap<(State, Action), f32>> {
+        let contents = fs::read_to_string(path).ok()?;
+        let persisted: PersistedTable = serde_json::from_str(&contents).ok()?;
+        Some(
+            persisted
+                .entries
+                .into_iter()
+                .map(|e| ((e.state, e.action), e.value))
+                .collect(),
+        )
+    }
+
+    fn persist(&self) {
+        let table = match self.table.read() {
+            Ok(t) => t,
+            Err(_) => return,
+        };
+        let entries: Vec<QEntry> = table
+            .iter()
+            .map(|(&(state, action), &value)| QEntry { state, action, value })
+            .collect();
+        drop(table);
+
+        let json = match serde_json::to_string_pretty(&PersistedTable { entries }) {
+            Ok(j) => j,
+            Err(e) => {
+                tracing::error!("Failed to serialize Q-table: {}", e);
+                return;
+            }
+        };
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Err(e) = fs::write(&self.path, json) {
+            tracing::error!("Failed to persist Q-table to {:?}: {}", self.path, e);
+        }
+    }
+
+    fn q(&self, state: State, action: Action) -> f32 {
+        self.table.read().unwrap().get(&(state, action)).copied().unwrap_or(0.0)
+    }
+
+    fn max_q(&self, state: State) -> f32 {
+        Action::ALL
+            .iter()
+            .map(|&action| self.q(state, action))
+            .fold(f32::MIN, f32::max)
+    }
+
+    /// Epsilon-greedy action selection: usually the best-known action for
+    /// `state`, occasionally a random one so the policy keeps exploring
+    pub fn choose_action(&self, state: State) -> Action {
+        if next_random_unit() < EPSILON {
+            let idx = (next_random_unit() * Action::ALL.len() as f32) as usize % Action::ALL.len();
+            return Action::ALL[idx];
+        }
+        Action::ALL
+            .iter()
+            .copied()
+            .max_by(|&a, &b| {
+                self.q(state, a)
+                    .partial_cmp(&self.q(state, b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(Action::Neutral)
+    }
+
+    /// Apply the Q-learning update for the transition `state --action-->
+    /// next_state` that earned `reward`, then persist the table
+    pub fn learn(&self, state: State, action: Action, reward: f32, next_state: State) {
+        let max_next = self.max_q(next_state);
+        {
+            let mut table = self.table.write().unwrap();
+            let current = table.get(&(state, action)).copied().unwrap_or(0.0);
+            let updated = current + ALPHA * (reward + GAMMA * max_next - current);
+            table.insert((state, action), updated);
+        }
+        self.persist();
+    }
+}
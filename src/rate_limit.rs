@@ -0,0 +1,124 @@
+use axum::http::{HeaderMap, Method};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// Coarse request classes so reads, writes, and destructive bulk operations
+/// can be throttled independently instead of sharing one global budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperationClass {
+    Read,
+    Write,
+    Bulk,
+}
+
+impl OperationClass {
+    /// Classify a request by method and path: `/clear`/`/purge` routes are
+    /// bulk (most destructive), `GET` is a read, everything else is a write
+    pub fn classify(method: &Method, path: &str) -> Self {
+        if path.ends_with("/clear") || path.ends_with("/purge") {
+            OperationClass::Bulk
+        } else if method == Method::GET {
+            OperationClass::Read
+        } else {
+            OperationClass::Write
+        }
+    }
+
+    fn limit(self, limits: &RateLimits) -> f64 {
+        match self {
+            OperationClass::Read => limits.read_per_min,
+            OperationClass::Write => limits.write_per_min,
+            OperationClass::Bulk => limits.bulk_per_min,
+        }
+    }
+}
+
+/// Per-class token bucket capacity, in requests per minute. Also used as the
+/// refill rate, so a bucket fully recovers to capacity once per minute.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimits {
+    pub read_per_min: f64,
+    pub write_per_min: f64,
+    pub bulk_per_min: f64,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Token-bucket rate limiter keyed by `(client, operation class)`, so a
+/// client that exhausts its bulk/clear budget can still make ordinary reads
+/// and writes. Mirrors `SessionRegistry`'s `RwLock<HashMap<...>>` pattern for
+/// shared, frequently-read-rarely-evicted state.
+pub struct RateLimiter {
+    limits: RateLimits,
+    buckets: RwLock<HashMap<(String, OperationClass), Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        Self {
+            limits,
+            buckets: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Try to consume one token from `client`'s bucket for `class`.
+    /// `Ok(())` if allowed; `Err(retry_after_secs)` if the bucket is empty.
+    pub fn check(&self, client: &str, class: OperationClass) -> Result<(), u64> {
+        let capacity = class.limit(&self.limits);
+        let refill_per_sec = capacity / 60.0;
+        let key = (client.to_string(), class);
+
+        let mut buckets = self.buckets.write().unwrap();
+        let bucket = buckets.entry(key).or_insert_with(|| Bucket {
+            tokens: capacity,
+            last_refill: Instant::now(),
+        });
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let deficit = 1.0 - bucket.tokens;
+            Err(((deficit / refill_per_sec).ceil() as u64).max(1))
+        }
+    }
+
+    /// Drop buckets untouched for longer than `idle_after`, so memory stays
+    /// bounded no matter how many distinct clients (IPs, API keys) show up
+    pub fn evict_idle(&self, idle_after: Duration) {
+        let now = Instant::now();
+        self.buckets
+            .write()
+            .unwrap()
+            .retain(|_, bucket| now.duration_since(bucket.last_refill) < idle_after);
+    }
+}
+
+/// Identify the caller for rate limiting: an API key if present, otherwise
+/// the left-most address in `X-Forwarded-For` (the original client behind
+/// any proxies), otherwise a shared key for unidentified callers
+pub fn client_key(headers: &HeaderMap) -> String {
+    if let Some(key) = headers.get("X-API-Key").and_then(|v| v.to_str().ok()) {
+        return format!("key:{}", key);
+    }
+    if let Some(forwarded) = headers.get("X-Forwarded-For").and_then(|v| v.to_str().ok()) {
+        if let Some(first) = forwarded
+            .split(',')
+            .next()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            return format!("ip:{}", first);
+        }
+    }
+    "anonymous".to_string()
+}
@@ -0,0 +1,117 @@
+use crate::chat::ChatMessage;
+use axum::extract::ws::Message;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// Frame pushed to every subscriber right before a session's connections are dropped
+const SESSION_CLOSED_FRAME: &str = r#"{"type":"session_closed"}"#;
+
+/// A single live WebSocket connection subscribed to a chat session
+struct ConnectionHandle {
+    id: String,
+    sender: mpsc::UnboundedSender<Message>,
+    last_pong: u64,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Central registry of `session_id -> live connections`. New chat messages
+/// are broadcast to every connection subscribed to the session they belong
+/// to, so multiple clients can watch a session update live instead of
+/// polling `list_chat_sessions`/`get_chat_history`.
+#[derive(Default)]
+pub struct SessionRegistry {
+    connections: RwLock<HashMap<String, Vec<ConnectionHandle>>>,
+}
+
+impl SessionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribe a new connection to `session_id`, returning its connection
+    /// id (for a later `unsubscribe`) and the receiving end of its outbound queue
+    pub fn subscribe(&self, session_id: &str) -> (String, mpsc::UnboundedReceiver<Message>) {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.connections
+            .write()
+            .unwrap()
+            .entry(session_id.to_string())
+            .or_default()
+            .push(ConnectionHandle {
+                id: id.clone(),
+                sender,
+                last_pong: now_secs(),
+            });
+        (id, receiver)
+    }
+
+    /// Remove one connection from a session's subscriber list, e.g. after it disconnects
+    pub fn unsubscribe(&self, session_id: &str, connection_id: &str) {
+        let mut connections = self.connections.write().unwrap();
+        if let Some(handles) = connections.get_mut(session_id) {
+            handles.retain(|h| h.id != connection_id);
+            if handles.is_empty() {
+                connections.remove(session_id);
+            }
+        }
+    }
+
+    /// Record a pong received from `connection_id`, keeping it alive through the next prune
+    pub fn record_pong(&self, session_id: &str, connection_id: &str) {
+        let mut connections = self.connections.write().unwrap();
+        if let Some(handle) = connections
+            .get_mut(session_id)
+            .and_then(|handles| handles.iter_mut().find(|h| h.id == connection_id))
+        {
+            handle.last_pong = now_secs();
+        }
+    }
+
+    /// Broadcast `message` to every connection subscribed to `session_id`
+    pub fn broadcast(&self, session_id: &str, message: &ChatMessage) {
+        if let Ok(text) = serde_json::to_string(message) {
+            self.send_text(session_id, text);
+        }
+    }
+
+    /// Push a "session closed" frame to every subscriber of `session_id`, then drop them all
+    pub fn close_session(&self, session_id: &str) {
+        self.send_text(session_id, SESSION_CLOSED_FRAME.to_string());
+        self.connections.write().unwrap().remove(session_id);
+    }
+
+    fn send_text(&self, session_id: &str, text: String) {
+        let connections = self.connections.read().unwrap();
+        if let Some(handles) = connections.get(session_id) {
+            for handle in handles {
+                let _ = handle.sender.send(Message::Text(text.clone()));
+            }
+        }
+    }
+
+    /// Ping every live connection and drop any that hasn't ponged within
+    /// `stale_after`, so a half-open TCP connection doesn't linger in the registry forever
+    pub fn ping_and_prune(&self, stale_after: Duration) {
+        let now = now_secs();
+        let mut connections = self.connections.write().unwrap();
+        connections.retain(|_, handles| {
+            handles.retain(|h| {
+                let alive = now.saturating_sub(h.last_pong) < stale_after.as_secs();
+                if alive {
+                    let _ = h.sender.send(Message::Ping(Vec::new()));
+                }
+                alive
+            });
+            !handles.is_empty()
+        });
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+/// A document indexed for full-text search. `term_weights` holds the
+/// weighted term frequency (field weight summed per occurrence) so that a
+/// match in a higher-weighted field scores higher than the same match in a
+/// lower-weighted one.
+#[derive(Debug, Clone)]
+struct IndexedDocument {
+    id: String,
+    term_weights: HashMap<String, f32>,
+    length: f32,
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|w| w.to_lowercase().trim_matches(|c: char| !c.is_alphanumeric()).to_string())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Levenshtein edit distance between two strings, used for typo-tolerant matching
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut dp = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in dp.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for j in 0..=b.len() {
+        dp[0][j] = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            dp[i][j] = (dp[i - 1][j] + 1)
+                .min(dp[i][j - 1] + 1)
+                .min(dp[i - 1][j - 1] + cost);
+        }
+    }
+    dp[a.len()][b.len()]
+}
+
+/// Inverted index supporting BM25-ranked full-text search with simple typo
+/// tolerance: query terms within edit distance 1 of an indexed term
+/// (distance 2 for query terms of 8 or more characters) are treated as matches.
+pub struct InvertedIndex {
+    documents: Vec<IndexedDocument>,
+    postings: HashMap<String, Vec<usize>>,
+    average_doc_length: f32,
+    k1: f32,
+    b: f32,
+}
+
+impl Default for InvertedIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InvertedIndex {
+    pub fn new() -> Self {
+        Self {
+            documents: Vec::new(),
+            postings: HashMap::new(),
+            average_doc_length: 0.0,
+            k1: 1.5,
+            b: 0.75,
+        }
+    }
+
+    /// Build an index with custom BM25 parameters instead of the defaults
+    pub fn with_params(k1: f32, b: f32) -> Self {
+        Self {
+            k1,
+            b,
+            ..Self::new()
+        }
+    }
+
+    /// Index `text` under `id` as a single unweighted field, replacing any
+    /// existing entry with that id
+    pub fn index(&mut self, id: &str, text: &str) {
+        self.index_weighted(id, &[(text, 1.0)]);
+    }
+
+    /// Index multiple `(text, weight)` fields under `id`, replacing any
+    /// existing entry with that id. A match in a higher-weighted field (e.g.
+    /// a title) scores higher than the same match in a lower-weighted one
+    /// (e.g. a body).
+    pub fn index_weighted(&mut self, id: &str, fields: &[(&str, f32)]) {
+        self.documents.retain(|d| d.id != id);
+
+        let mut term_weights: HashMap<String, f32> = HashMap::new();
+        let mut length = 0.0f32;
+        for (text, weight) in fields {
+            let tokens = tokenize(text);
+            length += tokens.len() as f32 * weight;
+            for token in tokens {
+                *term_weights.entry(token).or_insert(0.0) += weight;
+            }
+        }
+
+        self.documents.push(IndexedDocument {
+            id: id.to_string(),
+            term_weights,
+            length,
+        });
+        self.rebuild_postings();
+    }
+
+    /// Remove the document with `id`, if indexed
+    pub fn remove(&mut self, id: &str) {
+        self.documents.retain(|d| d.id != id);
+        self.rebuild_postings();
+    }
+
+    fn rebuild_postings(&mut self) {
+        self.postings.clear();
+        for (idx, doc) in self.documents.iter().enumerate() {
+            for term in doc.term_weights.keys() {
+                self.postings.entry(term.clone()).or_default().push(idx);
+            }
+        }
+
+        let total_length: f32 = self.documents.iter().map(|d| d.length).sum();
+        self.average_doc_length = if self.documents.is_empty() {
+            0.0
+        } else {
+            total_length / self.documents.len() as f32
+        };
+    }
+
+    /// Indexed terms that match `term` exactly or within edit distance 1
+    /// (distance 2 for terms of 8 or more characters, where a typo is less
+    /// likely to collide with an unrelated word)
+    fn expand_term(&self, term: &str) -> Vec<&str> {
+        let max_distance = if term.chars().count() >= 8 { 2 } else { 1 };
+        self.postings
+            .keys()
+            .filter(|t| t.as_str() == term || edit_distance(t, term) <= max_distance)
+            .map(|t| t.as_str())
+            .collect()
+    }
+
+    /// Rank indexed documents against `query` using BM25, returning `(id, score)`
+    /// pairs sorted by descending score.
+    pub fn search(&self, query: &str, top_n: usize) -> Vec<(String, f32)> {
+        let query_terms = tokenize(query);
+        let n = self.documents.len() as f32;
+        let mut scores: HashMap<usize, f32> = HashMap::new();
+
+        for term in &query_terms {
+            for matched_term in self.expand_term(term) {
+                let doc_indices = match self.postings.get(matched_term) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                let df = doc_indices.len() as f32;
+                let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+
+                for &idx in doc_indices {
+                    let doc = &self.documents[idx];
+                    let tf = *doc.term_weights.get(matched_term).unwrap_or(&0.0);
+                    let doc_len = doc.length;
+                    let denom = tf
+                        + self.k1 * (1.0 - self.b + self.b * doc_len / self.average_doc_length.max(1.0));
+                    let score = idf * (tf * (self.k1 + 1.0)) / denom.max(1e-6);
+                    *scores.entry(idx).or_insert(0.0) += score;
+                }
+            }
+        }
+
+        let mut ranked: Vec<(String, f32)> = scores
+            .into_iter()
+            .map(|(idx, score)| (self.documents[idx].id.clone(), score))
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        ranked.truncate(top_n);
+        ranked
+    }
+
+    /// Number of indexed documents
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Whether the index has no documents
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+}
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// A token's contribution to each trait axis `SentimentLexicon::score` accumulates:
+/// valence moves happiness, interrogative moves curiosity, threat moves caution.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TokenWeights {
+    #[serde(default)]
+    pub valence: f32,
+    #[serde(default)]
+    pub interrogative: f32,
+    #[serde(default)]
+    pub threat: f32,
+}
+
+/// A token-to-weights map loaded from a JSON file, e.g. one per language.
+/// Replaces the `contains("halo")`-style hardcoding `Personality::update`
+/// used to do, so new words or languages are a data change here, not a
+/// source edit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentimentLexicon {
+    #[serde(flatten)]
+    terms: HashMap<String, TokenWeights>,
+}
+
+impl Default for SentimentLexicon {
+    fn default() -> Self {
+        Self::default_pack()
+    }
+}
+
+impl SentimentLexicon {
+    /// Load a single language pack: a flat JSON object mapping lowercase
+    /// tokens to `TokenWeights`
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let contents = fs::read_to_string(path.as_ref())
+            .map_err(|e| format!("Failed to read sentiment lexicon {:?}: {}", path.as_ref(), e))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse sentiment lexicon {:?}: {}", path.as_ref(), e))
+    }
+
+    /// Load and merge several language packs, later paths overriding earlier
+    /// ones on token collision. A pack that fails to load is skipped with a
+    /// warning rather than aborting the whole set, so one bad file doesn't
+    /// take down every language. Falls back to `default_pack` if every path
+    /// fails to load (including the empty-paths case).
+    pub fn load_packs<P: AsRef<Path>>(paths: &[P]) -> Self {
+        let mut terms = HashMap::new();
+        for path in paths {
+            match Self::load_from_file(path) {
+                Ok(pack) => terms.extend(pack.terms),
+                Err(e) => tracing::warn!("{}", e),
+            }
+        }
+        if terms.is_empty() {
+            return Self::default_pack();
+        }
+        Self { terms }
+    }
+
+    /// Built-in fallback covering the Indonesian/English terms
+    /// `Personality::update` used to hardcode, so behavior doesn't regress
+    /// when no lexicon files are configured
+    pub fn default_pack() -> Self {
+        let mut terms = HashMap::new();
+        let mut set = |token: &str, valence: f32, interrogative: f32, threat: f32| {
+            terms.insert(
+                token.to_string(),
+                TokenWeights { valence, interrogative, threat },
+            );
+        };
+        set("halo", 1.0, 0.0, 0.0);
+        set("hello", 1.0, 0.0, 0.0);
+        set("terima", 0.5, 0.0, 0.0);
+        set("kasih", 0.5, 0.0, 0.0);
+        set("apa", 0.0, 1.0, 0.0);
+        set("mengapa", 0.0, 1.0, 0.0);
+        set("bagaimana", 0.0, 1.0, 0.0);
+        set("bahaya", 0.0, 0.0, 1.0);
+        set("error", 0.0, 0.0, 1.0);
+        set("warning", 0.0, 0.0, 1.0);
+        Self { terms }
+    }
+
+    /// Tokenize `input` and accumulate each recognized token's weights into a `SentimentScore`
+    pub fn score(&self, input: &str) -> SentimentScore {
+        let mut score = SentimentScore::default();
+        for raw in input.split_whitespace() {
+            let lower = raw.to_lowercase();
+            let token = lower.trim_matches(|c: char| !c.is_alphanumeric());
+            if let Some(weights) = self.terms.get(token) {
+                score.valence += weights.valence;
+                score.interrogative += weights.interrogative;
+                score.threat += weights.threat;
+            }
+        }
+        score
+    }
+}
+
+/// Accumulated lexicon weights for one piece of input text, broken out by
+/// trait axis so callers can see why a trait moved
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SentimentScore {
+    pub valence: f32,
+    pub interrogative: f32,
+    pub threat: f32,
+}
@@ -0,0 +1,187 @@
+use crate::chat::{ChatMessage, ChatSession};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Result of a cursor-paginated history query
+pub enum HistoryPage {
+    Ok {
+        messages: Vec<ChatMessage>,
+        next_cursor: Option<String>,
+        prev_cursor: Option<String>,
+    },
+    SessionNotFound,
+    AnchorNotFound,
+}
+
+/// Persists chat sessions to disk and serves cursor-paginated history queries
+pub struct SessionStore {
+    path: PathBuf,
+    sessions: RwLock<HashMap<String, ChatSession>>,
+}
+
+impl SessionStore {
+    /// Load sessions from `path` if it exists, otherwise start empty
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let sessions = Self::load(&path).unwrap_or_default();
+        Self {
+            path,
+            sessions: RwLock::new(sessions),
+        }
+    }
+
+    fn load(path: &PathBuf) -> Option<HashMap<String, ChatSession>> {
+        let contents = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    fn persist(&self) {
+        let sessions = match self.sessions.read() {
+            Ok(s) => s,
+            Err(_) => return,
+        };
+        let json = match serde_json::to_string_pretty(&*sessions) {
+            Ok(j) => j,
+            Err(e) => {
+                tracing::error!("Failed to serialize chat sessions: {}", e);
+                return;
+            }
+        };
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Err(e) = fs::write(&self.path, json) {
+            tracing::error!("Failed to persist chat sessions to {:?}: {}", self.path, e);
+        }
+    }
+
+    /// Append a message, creating the session if it doesn't exist yet, and persist
+    pub fn add_message(&self, session_id: &str, message: ChatMessage) {
+        {
+            let mut sessions = self.sessions.write().unwrap();
+            let session = sessions
+                .entry(session_id.to_string())
+                .or_insert_with(|| ChatSession::new(session_id));
+            session.add_message(message);
+        }
+        self.persist();
+    }
+
+    pub fn get(&self, session_id: &str) -> Option<ChatSession> {
+        self.sessions.read().unwrap().get(session_id).cloned()
+    }
+
+    pub fn list_ids(&self) -> Vec<String> {
+        self.sessions.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Session ids ordered by creation time (tie-broken by id), windowed by
+    /// before/after cursors (each a session id, exclusive) and capped at
+    /// `limit`, CHATHISTORY-style.
+    pub fn paginate_ids(
+        &self,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<String>, Option<String>, Option<String>), String> {
+        let sessions = self.sessions.read().unwrap();
+        let mut ids: Vec<&ChatSession> = sessions.values().collect();
+        ids.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        let mut ids: Vec<String> = ids.into_iter().map(|s| s.id.clone()).collect();
+
+        if let Some(cursor) = before {
+            let idx = ids
+                .iter()
+                .position(|id| id == cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            ids.truncate(idx);
+        }
+        if let Some(cursor) = after {
+            let idx = ids
+                .iter()
+                .position(|id| id == cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            ids = ids.split_off(idx + 1);
+        }
+
+        let page = if after.is_none() {
+            let skip = ids.len().saturating_sub(limit);
+            ids.split_off(skip)
+        } else {
+            ids.truncate(limit);
+            ids
+        };
+
+        let next_cursor = page.last().cloned();
+        let prev_cursor = page.first().cloned();
+        Ok((page, next_cursor, prev_cursor))
+    }
+
+    /// Remove a session entirely, returning whether it existed
+    pub fn remove(&self, session_id: &str) -> bool {
+        let removed = self.sessions.write().unwrap().remove(session_id).is_some();
+        if removed {
+            self.persist();
+        }
+        removed
+    }
+
+    /// Messages ordered by timestamp, with `id` as a tiebreaker for equal timestamps
+    fn sorted_messages(session: &ChatSession) -> Vec<ChatMessage> {
+        let mut messages = session.messages.clone();
+        messages.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.id.cmp(&b.id)));
+        messages
+    }
+
+    /// A cursor-paginated window of messages: `before`/`after` are message
+    /// ids marking exclusive bounds, and the result carries cursors for the
+    /// adjacent pages so clients can scroll incrementally.
+    pub fn page(
+        &self,
+        session_id: &str,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> HistoryPage {
+        let sessions = self.sessions.read().unwrap();
+        let session = match sessions.get(session_id) {
+            Some(s) => s,
+            None => return HistoryPage::SessionNotFound,
+        };
+        let mut messages = Self::sorted_messages(session);
+
+        if let Some(anchor) = before {
+            match messages.iter().position(|m| m.id == anchor) {
+                Some(idx) => messages.truncate(idx),
+                None => return HistoryPage::AnchorNotFound,
+            }
+        }
+        if let Some(anchor) = after {
+            match messages.iter().position(|m| m.id == anchor) {
+                Some(idx) => messages = messages.split_off(idx + 1),
+                None => return HistoryPage::AnchorNotFound,
+            }
+        }
+
+        // With no `after` cursor (including no cursor at all, i.e. "latest"),
+        // the page is the tail of the remaining window; an `after` cursor
+        // walks forward, so the page is the head instead.
+        let page = if after.is_none() {
+            let skip = messages.len().saturating_sub(limit);
+            messages.split_off(skip)
+        } else {
+            messages.truncate(limit);
+            messages
+        };
+
+        let next_cursor = page.last().map(|m| m.id.clone());
+        let prev_cursor = page.first().map(|m| m.id.clone());
+        HistoryPage::Ok {
+            messages: page,
+            next_cursor,
+            prev_cursor,
+        }
+    }
+}
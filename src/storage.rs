@@ -0,0 +1,709 @@
+use crate::chat::{ApiLearningRecord, ChatMessage, ChatSession};
+use crate::embedding::{self, EmbeddingProvider};
+use crate::experience::Experience;
+use crate::experience_store::ExperienceStore;
+use crate::learning_store::{next_version, LearningRecordStore, RecordVersion};
+use crate::search::InvertedIndex;
+use crate::session_store::{HistoryPage, SessionStore};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+/// Persistence boundary for learning records and chat sessions. Handlers
+/// depend only on this trait, not on a concrete backend, so the backend can
+/// be swapped (in-memory for tests, disk-backed today, a real database
+/// tomorrow) without touching a single handler.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn get_record(&self, id: &str) -> Option<ApiLearningRecord>;
+    async fn put_record(&self, record: ApiLearningRecord);
+    async fn delete_record(&self, id: &str) -> bool;
+    async fn list_records(&self) -> Vec<ApiLearningRecord>;
+    async fn search_records(&self, query: &str, top_n: usize) -> Vec<(ApiLearningRecord, f32)>;
+    /// Cursor-paginated, optionally filtered (`tag`, `created_after`) listing
+    /// of records, sorted by `learned_at` (descending if `descending`)
+    async fn list_records_page(
+        &self,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+        tag: Option<&str>,
+        created_after: Option<chrono::DateTime<chrono::Utc>>,
+        descending: bool,
+    ) -> Result<(Vec<ApiLearningRecord>, Option<String>, Option<String>), String>;
+    /// Cursor-paginated BM25 search, score descending
+    async fn search_records_page(
+        &self,
+        query: &str,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<(ApiLearningRecord, f32)>, Option<String>, Option<String>), String>;
+    /// Rank records by cosine similarity to `query`'s embedding, keeping
+    /// only matches scoring at or above `threshold`
+    async fn search_semantic(
+        &self,
+        query: &str,
+        top_n: usize,
+        threshold: f32,
+    ) -> Result<Vec<(ApiLearningRecord, f32)>, String>;
+    /// Full version history for a record, oldest first, or `None` if it has never existed
+    async fn list_versions(&self, id: &str) -> Option<Vec<RecordVersion>>;
+    /// Reinstate the last non-deleted version of a tombstoned record
+    async fn restore_record(&self, id: &str) -> Option<ApiLearningRecord>;
+    /// Soft-delete every live record (tombstone, not erase)
+    async fn clear_records(&self) -> usize;
+    /// Permanently erase every version of every record; unlike `clear_records`
+    /// this cannot be undone with `restore_record`
+    async fn purge_records(&self) -> usize;
+
+    /// Persist a newly created experience
+    async fn add_experience(&self, experience: Experience);
+    /// Erase every stored experience, returning how many were removed
+    async fn clear_experiences(&self) -> usize;
+
+    async fn get_session(&self, session_id: &str) -> Option<ChatSession>;
+    async fn add_message(&self, session_id: &str, message: ChatMessage);
+    async fn list_session_ids(&self) -> Vec<String>;
+    /// Cursor-paginated listing of session ids, ordered by creation time
+    async fn list_sessions_page(
+        &self,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<String>, Option<String>, Option<String>), String>;
+    async fn delete_session(&self, session_id: &str) -> bool;
+    async fn history_page(
+        &self,
+        session_id: &str,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> HistoryPage;
+}
+
+/// Rank `(record, embedding)` pairs by cosine similarity to `query_embedding`
+/// (a plain dot product since every cached embedding is pre-normalized),
+/// keeping only matches at or above `threshold` and returning the top `top_n`
+fn rank_by_embedding(
+    query_embedding: &[f32],
+    candidates: Vec<(ApiLearningRecord, Vec<f32>)>,
+    top_n: usize,
+    threshold: f32,
+) -> Vec<(ApiLearningRecord, f32)> {
+    let mut scored: Vec<(ApiLearningRecord, f32)> = candidates
+        .into_iter()
+        .map(|(record, vector)| {
+            let score = embedding::dot(query_embedding, &vector);
+            (record, score)
+        })
+        .filter(|(_, score)| *score >= threshold)
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    scored.truncate(top_n);
+    scored
+}
+
+/// Disk-backed `Storage`, delegating to the existing `LearningRecordStore`
+/// and `SessionStore` JSON-file backends
+pub struct DiskStorage {
+    records: LearningRecordStore,
+    sessions: SessionStore,
+    experiences: ExperienceStore,
+    embedding_provider: Arc<dyn EmbeddingProvider>,
+}
+
+impl DiskStorage {
+    pub fn new(
+        records_path: impl Into<PathBuf>,
+        sessions_path: impl Into<PathBuf>,
+        embedding_provider: Arc<dyn EmbeddingProvider>,
+    ) -> Self {
+        Self::with_experiences_path(records_path, sessions_path, "data/experiences.json", embedding_provider)
+    }
+
+    pub fn with_experiences_path(
+        records_path: impl Into<PathBuf>,
+        sessions_path: impl Into<PathBuf>,
+        experiences_path: impl Into<PathBuf>,
+        embedding_provider: Arc<dyn EmbeddingProvider>,
+    ) -> Self {
+        Self {
+            records: LearningRecordStore::new(records_path),
+            sessions: SessionStore::new(sessions_path),
+            experiences: ExperienceStore::new(experiences_path),
+            embedding_provider,
+        }
+    }
+
+    /// Compute, normalize, and cache an embedding for `record` if the
+    /// provider is configured. Best-effort: a failed or disabled provider
+    /// just leaves the record out of semantic search results.
+    async fn reindex_embedding(&self, record: &ApiLearningRecord) {
+        if !self.embedding_provider.is_enabled() {
+            return;
+        }
+        match self.embedding_provider.embed(&record.summary).await {
+            Ok(mut vector) => {
+                embedding::normalize(&mut vector);
+                self.records.cache_embedding(&record.id, vector);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to embed learning record {}: {}", record.id, e);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for DiskStorage {
+    async fn get_record(&self, id: &str) -> Option<ApiLearningRecord> {
+        self.records.get(id)
+    }
+
+    async fn put_record(&self, record: ApiLearningRecord) {
+        self.reindex_embedding(&record).await;
+        self.records.insert(record);
+    }
+
+    async fn delete_record(&self, id: &str) -> bool {
+        self.records.remove(id)
+    }
+
+    async fn list_records(&self) -> Vec<ApiLearningRecord> {
+        self.records.all()
+    }
+
+    async fn search_records(&self, query: &str, top_n: usize) -> Vec<(ApiLearningRecord, f32)> {
+        self.records.search(query, top_n)
+    }
+
+    async fn list_records_page(
+        &self,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+        tag: Option<&str>,
+        created_after: Option<chrono::DateTime<chrono::Utc>>,
+        descending: bool,
+    ) -> Result<(Vec<ApiLearningRecord>, Option<String>, Option<String>), String> {
+        self.records
+            .paginate(before, after, limit, tag, created_after, descending)
+    }
+
+    async fn search_records_page(
+        &self,
+        query: &str,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<(ApiLearningRecord, f32)>, Option<String>, Option<String>), String> {
+        self.records.search_page(query, before, after, limit)
+    }
+
+    async fn search_semantic(
+        &self,
+        query: &str,
+        top_n: usize,
+        threshold: f32,
+    ) -> Result<Vec<(ApiLearningRecord, f32)>, String> {
+        let mut query_embedding = self.embedding_provider.embed(query).await?;
+        embedding::normalize(&mut query_embedding);
+        Ok(rank_by_embedding(&query_embedding, self.records.embeddings(), top_n, threshold))
+    }
+
+    async fn list_versions(&self, id: &str) -> Option<Vec<RecordVersion>> {
+        self.records.versions(id)
+    }
+
+    async fn restore_record(&self, id: &str) -> Option<ApiLearningRecord> {
+        let restored = self.records.restore(id)?;
+        self.reindex_embedding(&restored).await;
+        Some(restored)
+    }
+
+    async fn clear_records(&self) -> usize {
+        self.records.clear()
+    }
+
+    async fn purge_records(&self) -> usize {
+        self.records.purge()
+    }
+
+    async fn add_experience(&self, experience: Experience) {
+        self.experiences.add(experience);
+    }
+
+    async fn clear_experiences(&self) -> usize {
+        self.experiences.clear()
+    }
+
+    async fn get_session(&self, session_id: &str) -> Option<ChatSession> {
+        self.sessions.get(session_id)
+    }
+
+    async fn add_message(&self, session_id: &str, message: ChatMessage) {
+        self.sessions.add_message(session_id, message);
+    }
+
+    async fn list_session_ids(&self) -> Vec<String> {
+        self.sessions.list_ids()
+    }
+
+    async fn list_sessions_page(
+        &self,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<String>, Option<String>, Option<String>), String> {
+        self.sessions.paginate_ids(before, after, limit)
+    }
+
+    async fn delete_session(&self, session_id: &str) -> bool {
+        self.sessions.remove(session_id)
+    }
+
+    async fn history_page(
+        &self,
+        session_id: &str,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> HistoryPage {
+        self.sessions.page(session_id, before, after, limit)
+    }
+}
+
+/// BM25 parameters matching `LearningRecordStore`'s, so search behavior is
+/// the same regardless of which `Storage` backend is active
+const SEARCH_K1: f32 = 1.2;
+const SEARCH_B: f32 = 0.75;
+const TAGS_WEIGHT: f32 = 3.0;
+const SUMMARY_WEIGHT: f32 = 1.5;
+const URL_WEIGHT: f32 = 1.0;
+
+/// An `EmbeddingProvider` that always reports itself as disabled, matching
+/// `InMemoryStorage`'s "no external dependencies" design by default
+struct NoEmbeddingProvider;
+
+#[async_trait]
+impl EmbeddingProvider for NoEmbeddingProvider {
+    async fn embed(&self, _text: &str) -> Result<Vec<f32>, String> {
+        Err("No embedding provider configured for this storage backend".to_string())
+    }
+
+    fn is_enabled(&self) -> bool {
+        false
+    }
+}
+
+/// Pure in-memory `Storage`, with no disk I/O — used in tests and wherever
+/// persistence across restarts isn't needed
+pub struct InMemoryStorage {
+    records: RwLock<HashMap<String, ApiLearningRecord>>,
+    /// Append-only per-record version history, including tombstones left by soft deletes
+    history: RwLock<HashMap<String, Vec<RecordVersion>>>,
+    sessions: RwLock<HashMap<String, ChatSession>>,
+    embeddings: RwLock<HashMap<String, Vec<f32>>>,
+    experiences: RwLock<Vec<Experience>>,
+    embedding_provider: Arc<dyn EmbeddingProvider>,
+}
+
+impl Default for InMemoryStorage {
+    fn default() -> Self {
+        Self {
+            records: RwLock::new(HashMap::new()),
+            history: RwLock::new(HashMap::new()),
+            sessions: RwLock::new(HashMap::new()),
+            embeddings: RwLock::new(HashMap::new()),
+            experiences: RwLock::new(Vec::new()),
+            embedding_provider: Arc::new(NoEmbeddingProvider),
+        }
+    }
+}
+
+impl InMemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An in-memory store that computes embeddings via `embedding_provider`
+    /// instead of rejecting semantic search outright
+    pub fn with_embedding_provider(embedding_provider: Arc<dyn EmbeddingProvider>) -> Self {
+        Self {
+            embedding_provider,
+            ..Self::default()
+        }
+    }
+
+    /// Messages ordered by timestamp, with `id` as a tiebreaker for equal timestamps
+    fn sorted_messages(session: &ChatSession) -> Vec<ChatMessage> {
+        let mut messages = session.messages.clone();
+        messages.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.id.cmp(&b.id)));
+        messages
+    }
+}
+
+#[async_trait]
+impl Storage for InMemoryStorage {
+    async fn get_record(&self, id: &str) -> Option<ApiLearningRecord> {
+        self.records.read().unwrap().get(id).cloned()
+    }
+
+    async fn put_record(&self, record: ApiLearningRecord) {
+        if self.embedding_provider.is_enabled() {
+            match self.embedding_provider.embed(&record.summary).await {
+                Ok(mut vector) => {
+                    embedding::normalize(&mut vector);
+                    self.embeddings.write().unwrap().insert(record.id.clone(), vector);
+                }
+                Err(e) => tracing::warn!("Failed to embed learning record {}: {}", record.id, e),
+            }
+        }
+        {
+            let mut history = self.history.write().unwrap();
+            let entry = history.entry(record.id.clone()).or_default();
+            let version = next_version(entry);
+            entry.push(RecordVersion {
+                version,
+                record: Some(record.clone()),
+            });
+        }
+        self.records.write().unwrap().insert(record.id.clone(), record);
+    }
+
+    async fn delete_record(&self, id: &str) -> bool {
+        let removed = self.records.write().unwrap().remove(id).is_some();
+        if removed {
+            self.embeddings.write().unwrap().remove(id);
+            let mut history = self.history.write().unwrap();
+            if let Some(entry) = history.get_mut(id) {
+                let version = next_version(entry);
+                entry.push(RecordVersion {
+                    version,
+                    record: None,
+                });
+            }
+        }
+        removed
+    }
+
+    async fn list_versions(&self, id: &str) -> Option<Vec<RecordVersion>> {
+        self.history.read().unwrap().get(id).cloned()
+    }
+
+    async fn restore_record(&self, id: &str) -> Option<ApiLearningRecord> {
+        if self.records.read().unwrap().contains_key(id) {
+            return None;
+        }
+        let restored = {
+            let mut history = self.history.write().unwrap();
+            let entry = history.get_mut(id)?;
+            let last_live = entry.iter().rev().find_map(|v| v.record.clone())?;
+            let version = next_version(entry);
+            entry.push(RecordVersion {
+                version,
+                record: Some(last_live.clone()),
+            });
+            last_live
+        };
+        self.records.write().unwrap().insert(id.to_string(), restored.clone());
+        Some(restored)
+    }
+
+    async fn list_records(&self) -> Vec<ApiLearningRecord> {
+        self.records.read().unwrap().values().cloned().collect()
+    }
+
+    async fn search_records(&self, query: &str, top_n: usize) -> Vec<(ApiLearningRecord, f32)> {
+        let records = self.records.read().unwrap();
+        let mut index = InvertedIndex::with_params(SEARCH_K1, SEARCH_B);
+        for record in records.values() {
+            let tags_text = record.tags.join(" ");
+            index.index_weighted(
+                &record.id,
+                &[
+                    (record.url.as_str(), URL_WEIGHT),
+                    (tags_text.as_str(), TAGS_WEIGHT),
+                    (record.summary.as_str(), SUMMARY_WEIGHT),
+                ],
+            );
+        }
+        index
+            .search(query, top_n)
+            .into_iter()
+            .filter_map(|(id, score)| records.get(&id).map(|r| (r.clone(), score)))
+            .collect()
+    }
+
+    async fn list_records_page(
+        &self,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+        tag: Option<&str>,
+        created_after: Option<chrono::DateTime<chrono::Utc>>,
+        descending: bool,
+    ) -> Result<(Vec<ApiLearningRecord>, Option<String>, Option<String>), String> {
+        let records = self.records.read().unwrap();
+        let mut items: Vec<&ApiLearningRecord> = records
+            .values()
+            .filter(|r| tag.map_or(true, |t| r.tags.iter().any(|rt| rt == t)))
+            .filter(|r| created_after.map_or(true, |ts| r.learned_at > ts))
+            .collect();
+
+        if descending {
+            items.sort_by(|a, b| b.learned_at.cmp(&a.learned_at).then_with(|| b.id.cmp(&a.id)));
+        } else {
+            items.sort_by(|a, b| a.learned_at.cmp(&b.learned_at).then_with(|| a.id.cmp(&b.id)));
+        }
+
+        if let Some(cursor) = before {
+            let idx = items
+                .iter()
+                .position(|r| r.id == cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            items.truncate(idx);
+        }
+        if let Some(cursor) = after {
+            let idx = items
+                .iter()
+                .position(|r| r.id == cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            items = items.split_off(idx + 1);
+        }
+
+        let page: Vec<ApiLearningRecord> = if after.is_none() {
+            let skip = items.len().saturating_sub(limit);
+            items.split_off(skip).into_iter().cloned().collect()
+        } else {
+            items.truncate(limit);
+            items.into_iter().cloned().collect()
+        };
+
+        let next_cursor = page.last().map(|r| r.id.clone());
+        let prev_cursor = page.first().map(|r| r.id.clone());
+        Ok((page, next_cursor, prev_cursor))
+    }
+
+    async fn search_records_page(
+        &self,
+        query: &str,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<(ApiLearningRecord, f32)>, Option<String>, Option<String>), String> {
+        let records = self.records.read().unwrap();
+        let mut index = InvertedIndex::with_params(SEARCH_K1, SEARCH_B);
+        for record in records.values() {
+            let tags_text = record.tags.join(" ");
+            index.index_weighted(
+                &record.id,
+                &[
+                    (record.url.as_str(), URL_WEIGHT),
+                    (tags_text.as_str(), TAGS_WEIGHT),
+                    (record.summary.as_str(), SUMMARY_WEIGHT),
+                ],
+            );
+        }
+        let mut ranked: Vec<(ApiLearningRecord, f32)> = index
+            .search(query, usize::MAX)
+            .into_iter()
+            .filter_map(|(id, score)| records.get(&id).map(|r| (r.clone(), score)))
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.id.cmp(&b.0.id))
+        });
+
+        if let Some(cursor) = before {
+            let idx = ranked
+                .iter()
+                .position(|(r, _)| r.id == cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            ranked.truncate(idx);
+        }
+        if let Some(cursor) = after {
+            let idx = ranked
+                .iter()
+                .position(|(r, _)| r.id == cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            ranked = ranked.split_off(idx + 1);
+        }
+
+        let page = if after.is_none() {
+            let skip = ranked.len().saturating_sub(limit);
+            ranked.split_off(skip)
+        } else {
+            ranked.truncate(limit);
+            ranked
+        };
+
+        let next_cursor = page.last().map(|(r, _)| r.id.clone());
+        let prev_cursor = page.first().map(|(r, _)| r.id.clone());
+        Ok((page, next_cursor, prev_cursor))
+    }
+
+    async fn search_semantic(
+        &self,
+        query: &str,
+        top_n: usize,
+        threshold: f32,
+    ) -> Result<Vec<(ApiLearningRecord, f32)>, String> {
+        let mut query_embedding = self.embedding_provider.embed(query).await?;
+        embedding::normalize(&mut query_embedding);
+
+        let records = self.records.read().unwrap();
+        let embeddings = self.embeddings.read().unwrap();
+        let candidates: Vec<(ApiLearningRecord, Vec<f32>)> = embeddings
+            .iter()
+            .filter_map(|(id, vector)| records.get(id).map(|r| (r.clone(), vector.clone())))
+            .collect();
+
+        Ok(rank_by_embedding(&query_embedding, candidates, top_n, threshold))
+    }
+
+    async fn clear_records(&self) -> usize {
+        let mut records = self.records.write().unwrap();
+        let mut history = self.history.write().unwrap();
+        let mut count = 0;
+        for id in records.keys() {
+            if let Some(entry) = history.get_mut(id) {
+                let version = next_version(entry);
+                entry.push(RecordVersion {
+                    version,
+                    record: None,
+                });
+                count += 1;
+            }
+        }
+        records.clear();
+        self.embeddings.write().unwrap().clear();
+        count
+    }
+
+    async fn purge_records(&self) -> usize {
+        let mut history = self.history.write().unwrap();
+        let count = history.len();
+        history.clear();
+        self.records.write().unwrap().clear();
+        self.embeddings.write().unwrap().clear();
+        count
+    }
+
+    async fn add_experience(&self, experience: Experience) {
+        self.experiences.write().unwrap().push(experience);
+    }
+
+    async fn clear_experiences(&self) -> usize {
+        let mut experiences = self.experiences.write().unwrap();
+        let count = experiences.len();
+        experiences.clear();
+        count
+    }
+
+    async fn get_session(&self, session_id: &str) -> Option<ChatSession> {
+        self.sessions.read().unwrap().get(session_id).cloned()
+    }
+
+    async fn add_message(&self, session_id: &str, message: ChatMessage) {
+        let mut sessions = self.sessions.write().unwrap();
+        let session = sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| ChatSession::new(session_id));
+        session.add_message(message);
+    }
+
+    async fn list_session_ids(&self) -> Vec<String> {
+        self.sessions.read().unwrap().keys().cloned().collect()
+    }
+
+    async fn list_sessions_page(
+        &self,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<(Vec<String>, Option<String>, Option<String>), String> {
+        let sessions = self.sessions.read().unwrap();
+        let mut ids: Vec<&ChatSession> = sessions.values().collect();
+        ids.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        let mut ids: Vec<String> = ids.into_iter().map(|s| s.id.clone()).collect();
+
+        if let Some(cursor) = before {
+            let idx = ids
+                .iter()
+                .position(|id| id == cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            ids.truncate(idx);
+        }
+        if let Some(cursor) = after {
+            let idx = ids
+                .iter()
+                .position(|id| id == cursor)
+                .ok_or_else(|| format!("Unknown cursor: {}", cursor))?;
+            ids = ids.split_off(idx + 1);
+        }
+
+        let page = if after.is_none() {
+            let skip = ids.len().saturating_sub(limit);
+            ids.split_off(skip)
+        } else {
+            ids.truncate(limit);
+            ids
+        };
+
+        let next_cursor = page.last().cloned();
+        let prev_cursor = page.first().cloned();
+        Ok((page, next_cursor, prev_cursor))
+    }
+
+    async fn delete_session(&self, session_id: &str) -> bool {
+        self.sessions.write().unwrap().remove(session_id).is_some()
+    }
+
+    async fn history_page(
+        &self,
+        session_id: &str,
+        before: Option<&str>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> HistoryPage {
+        let sessions = self.sessions.read().unwrap();
+        let session = match sessions.get(session_id) {
+            Some(s) => s,
+            None => return HistoryPage::SessionNotFound,
+        };
+        let mut messages = Self::sorted_messages(session);
+
+        if let Some(anchor) = before {
+            match messages.iter().position(|m| m.id == anchor) {
+                Some(idx) => messages.truncate(idx),
+                None => return HistoryPage::AnchorNotFound,
+            }
+        }
+        if let Some(anchor) = after {
+            match messages.iter().position(|m| m.id == anchor) {
+                Some(idx) => messages = messages.split_off(idx + 1),
+                None => return HistoryPage::AnchorNotFound,
+            }
+        }
+
+        let page = if after.is_none() {
+            let skip = messages.len().saturating_sub(limit);
+            messages.split_off(skip)
+        } else {
+            messages.truncate(limit);
+            messages
+        };
+
+        let next_cursor = page.last().map(|m| m.id.clone());
+        let prev_cursor = page.first().map(|m| m.id.clone());
+        HistoryPage::Ok {
+            messages: page,
+            next_cursor,
+            prev_cursor,
+        }
+    }
+}